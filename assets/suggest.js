@@ -0,0 +1,23 @@
+// Typeahead for inputs with a data-suggest kind, filled from /api/v1/suggest
+document.querySelectorAll("input[data-suggest]").forEach(input => {
+	const list = document.createElement("datalist");
+	list.id = input.id + "-suggestions";
+	input.setAttribute("list", list.id);
+	input.setAttribute("autocomplete", "off");
+	input.after(list);
+
+	let timer;
+	input.addEventListener("input", () => {
+		clearTimeout(timer);
+		timer = setTimeout(async () => {
+			const params = new URLSearchParams({ kind: input.dataset.suggest, q: input.value });
+			const res = await fetch("/api/v1/suggest?" + params);
+			if (!res.ok) return;
+			list.replaceChildren(...(await res.json()).map(s => {
+				const option = document.createElement("option");
+				option.value = s;
+				return option;
+			}));
+		}, 150);
+	});
+});
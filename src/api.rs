@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use axum::{
+	extract::{Path, Query, State},
+	http::StatusCode,
+	Json,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::app::*;
+use crate::data::{Aspect, Recipe, Skill};
+use crate::logic::{suggest, SuggestKind};
+use crate::report::*;
+use crate::search::{search as find, SearchResult};
+
+/// JSON results for `/api/v1`, or an error status with a message
+pub type ApiResult<T> = Result<Json<T>, (StatusCode, Json<ApiError>)>;
+
+#[derive(Serialize, Debug)]
+pub struct ApiError {
+	pub error: String,
+}
+
+fn not_found<T>(res: Result<T, String>) -> ApiResult<T> {
+	res.map(Json).map_err(|error| (StatusCode::NOT_FOUND, Json(ApiError { error })))
+}
+
+/// A 400 for queries that can't be understood, or a 404 when nothing matches
+fn query_result<T>(res: Result<T, QueryError>) -> ApiResult<T> {
+	res.map(Json).map_err(|e| {
+		let status = match e {
+			QueryError::Invalid(_) => StatusCode::BAD_REQUEST,
+			QueryError::NotFound(_) => StatusCode::NOT_FOUND,
+		};
+		(status, Json(ApiError { error: e.to_string() }))
+	})
+}
+
+pub async fn memories(
+	State(state): State<AppState>,
+	Query(input): Query<PInput>,
+) -> Json<MemoryReport> {
+	Json(MemoryReport::new(&input.principle, &state.data, &state.save.lock().unwrap()))
+}
+
+pub async fn solve(
+	State(state): State<AppState>,
+	Query(input): Query<SInput>,
+) -> Json<SolveReport> {
+	Json(SolveReport::new(&input.p1, &input.p2, &state.data, &state.save.lock().unwrap()))
+}
+
+pub async fn solve_slots(
+	State(state): State<AppState>,
+	Query(input): Query<SlotInput>,
+) -> ApiResult<SlotReport> {
+	query_result(SlotReport::new(&input.workstation, &input.target, &state.data, &state.save.lock().unwrap()))
+}
+
+pub async fn crafting(
+	State(state): State<AppState>,
+	Query(input): Query<CInput>,
+) -> ApiResult<CraftingReport> {
+	query_result(CraftingReport::new(&input.skill, &state.data, &state.save.lock().unwrap()))
+}
+
+pub async fn plan(
+	State(state): State<AppState>,
+	Query(input): Query<PlInput>,
+) -> ApiResult<PlanReport> {
+	query_result(PlanReport::new(&input.goal, &state.data, &state.save.lock().unwrap()))
+}
+
+pub async fn items(
+	State(state): State<AppState>,
+	Query(input): Query<IInput>,
+) -> ApiResult<Vec<ItemReport>> {
+	let save = state.save.lock().unwrap();
+	query_result(ItemReport::find(&input.principles, &input.sort, input.owned, input.variants, &state.data, &save))
+}
+
+pub async fn skills(State(state): State<AppState>) -> Json<Vec<Skill>> {
+	let mut skills: Vec<_> = state.data.skills.values().cloned().collect();
+	skills.sort_by(|a, b| a.label.cmp(&b.label));
+	Json(skills)
+}
+
+pub async fn aspects(State(state): State<AppState>) -> Json<HashMap<String, Aspect>> {
+	Json(state.data.aspects.clone())
+}
+
+pub async fn workstations(
+	State(state): State<AppState>,
+	Query(input): Query<WInput>,
+) -> ApiResult<WorkstationsReport> {
+	query_result(WorkstationsReport::new(&input.principle, &input.soul, &input.wisdom, input.unlocked, &state.data, &state.save.lock().unwrap()))
+}
+
+pub async fn library(State(state): State<AppState>) -> Json<LibraryReport> {
+	Json(LibraryReport::new(&state.data, &state.save.lock().unwrap()))
+}
+
+pub async fn visitors(State(state): State<AppState>) -> Json<VisitorsReport> {
+	Json(VisitorsReport::new(&state.data, &state.save.lock().unwrap()))
+}
+
+pub async fn assistance(State(state): State<AppState>) -> Json<AssistanceReport> {
+	Json(AssistanceReport::new(&state.data, &state.save.lock().unwrap()))
+}
+
+pub async fn recipes(State(state): State<AppState>) -> Json<Vec<Recipe>> {
+	Json(state.data.all_recipes().cloned().collect())
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SuggestInput {
+	pub kind: SuggestKind,
+	#[serde(default)]
+	pub q: String,
+}
+
+pub async fn suggestions(
+	State(state): State<AppState>,
+	Query(input): Query<SuggestInput>,
+) -> Json<Vec<String>> {
+	Json(suggest(&state.data, input.kind, &input.q, 20))
+}
+
+pub async fn search(
+	State(state): State<AppState>,
+	Query(input): Query<SearchInput>,
+) -> Json<Vec<SearchResult>> {
+	Json(find(&state.data, &input.q, 50))
+}
+
+pub async fn item(
+	State(state): State<AppState>,
+	Path(id): Path<String>,
+) -> ApiResult<ItemDetail> {
+	not_found(ItemDetail::new(&id, &state.data, &state.save.lock().unwrap()).ok_or(format!("Item not found: {}", id)))
+}
+
+pub async fn book(
+	State(state): State<AppState>,
+	Path(id): Path<String>,
+) -> ApiResult<BookDetail> {
+	not_found(BookDetail::new(&id, &state.data, &state.save.lock().unwrap()).ok_or(format!("Book not found: {}", id)))
+}
+
+pub async fn skill(
+	State(state): State<AppState>,
+	Path(id): Path<String>,
+) -> ApiResult<SkillDetail> {
+	not_found(SkillDetail::new(&id, &state.data, &state.save.lock().unwrap()).ok_or(format!("Skill not found: {}", id)))
+}
+
+pub async fn workstation(
+	State(state): State<AppState>,
+	Path(id): Path<String>,
+) -> ApiResult<WorkstationDetail> {
+	not_found(WorkstationDetail::new(&id, &state.data, &state.save.lock().unwrap()).ok_or(format!("Workstation not found: {}", id)))
+}
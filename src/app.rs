@@ -1,18 +1,20 @@
-use std::collections::HashSet;
 use std::sync::{Arc, Mutex};
 
 use axum::{
-	extract::State,
+	extract::{Path, Query, State},
+	http::StatusCode,
 	response::IntoResponse,
 	Form,
 };
-use maud::{html, Markup, PreEscaped};
+use maud::{html, Markup};
 use serde::Deserialize;
 
-use crate::data::{Data, Recipe, RecipeLevel};
-use crate::logic::{dis_set, find_aspected, find_memories, get_skill_stations};
+use crate::data::{principles_from_soul, souls, Data};
+use crate::logic::matching_skills;
+use crate::report::*;
 use crate::save::SaveData;
-use crate::ui::base_layout;
+use crate::search::search;
+use crate::ui::{self, base_layout};
 
 #[derive(Clone)]
 pub struct AppState {
@@ -27,7 +29,7 @@ pub async fn root() -> Markup {
 pub async fn p_form() -> Markup {
 	base_layout("BoH Memories", html! {
 		form method="post" action="/find_mems" {
-			input .textbox type="text" name="principle" id="principle" placeholder="Principle";
+			input .textbox type="text" name="principle" id="principle" placeholder="lantern>=5, moth:2" data-suggest="principle";
 			input type="submit" value="Go";
 		}
 	})
@@ -35,28 +37,34 @@ pub async fn p_form() -> Markup {
 
 #[derive(Deserialize, Debug)]
 pub struct PInput {
-	principle: String,
+	pub principle: String,
 }
 
 pub async fn find_mems(
 	State(state): State<AppState>,
 	Form(input): Form<PInput>,
 ) -> impl IntoResponse {
-	let mems = find_memories(&[&input.principle], &state.save.lock().unwrap().items, &state.data.items, &state.data.books);
+	let report = MemoryReport::new(&input.principle, &state.data, &state.save.lock().unwrap());
 	drop(state.save);
-	let mut res = String::new();
-	for mem in mems {
-		res.push_str(&format!("<h3>{}</h3>", mem.0));
-		res.push_str(&format!("<p>{}</p>", dis_set(&mem.1)));
-	}
-	base_layout("BoH Memories", PreEscaped(res))
+	base_layout("BoH Memories", ui::memories(&report, &state.data))
 }
 
-pub async fn s_form() -> Markup {
+pub async fn s_form(State(state): State<AppState>) -> Markup {
 	base_layout("BoH Solver", html! {
 		form method="post" action="/solve" {
-			input .textbox type="text" name="p1" id="p1" placeholder="Principle";
-			input .textbox type="text" name="p2" id="p2" placeholder="Principle";
+			input .textbox type="text" name="p1" id="p1" placeholder="Principle, e.g. moth:2" data-suggest="principle";
+			input .textbox type="text" name="p2" id="p2" placeholder="Principle, e.g. lantern>=5" data-suggest="principle";
+			input type="submit" value="Go";
+		}
+		h2 { "Fill a workstation" }
+		form method="post" action="/solve/slots" {
+			select .textbox name="workstation" id="workstation" {
+				option value="" { "Any workstation" }
+				@for w in &state.data.workstations {
+					option value=(w.id) { (w.label) }
+				}
+			}
+			input .textbox type="text" name="target" id="target" placeholder="lantern>=10, recipe or book" data-suggest="target";
 			input type="submit" value="Go";
 		}
 	})
@@ -64,50 +72,42 @@ pub async fn s_form() -> Markup {
 
 #[derive(Deserialize, Debug)]
 pub struct SInput {
-	p1: String,
-	p2: String,
+	pub p1: String,
+	pub p2: String,
 }
 
 pub async fn solve(
 	State(state): State<AppState>,
 	Form(input): Form<SInput>,
 ) -> Markup {
-	let mut res = String::new();
-	let matching_skills: Vec<_> = state.data.skills.iter()
-		.filter(|(_, s)| s.matches(&[input.p1.clone(), input.p2.clone()]))
-		.map(|(_, s)| s).collect();
-
-	if matching_skills.is_empty() {
-		res.push_str("<h2>No Matching Skills</h2>");
-	} else {
-		res.push_str("<h2>Matching skills</h2>");
-		res.push_str(&get_skill_stations(&matching_skills, &state.data.workstations));
-	}
-
-	let mems = find_memories(
-		&[&input.p1, &input.p2],
-		&state.save.lock().unwrap().items,
-		&state.data.items,
-		&state.data.books
-	);
+	let report = SolveReport::new(&input.p1, &input.p2, &state.data, &state.save.lock().unwrap());
 	drop(state.save);
+	base_layout("BoH Solver", ui::solve(&report, &state.data))
+}
 
-	if mems.is_empty() {
-		res.push_str("<h2>No Matching Memories</h2>");
-	} else {
-		res.push_str("<h2>Matching Memories</h2>");
-		for (mem, v) in mems.iter() {
-			res.push_str(&format!("<h3>{}</h3>", mem));
-			res.push_str(&format!("<p>{}</p>", dis_set(v)));
-		}
+#[derive(Deserialize, Debug)]
+pub struct SlotInput {
+	#[serde(default)]
+	pub workstation: String,
+	pub target: String,
+}
+
+pub async fn solve_slots(
+	State(state): State<AppState>,
+	Form(input): Form<SlotInput>,
+) -> Markup {
+	let report = SlotReport::new(&input.workstation, &input.target, &state.data, &state.save.lock().unwrap());
+	drop(state.save);
+	match report {
+		Ok(r) => base_layout("BoH Solver", ui::slots(&r, &state.data)),
+		Err(e) => base_layout("BoH Solver", html!{ (e) }),
 	}
-	base_layout("BoH Solver", PreEscaped(res))
 }
 
 pub async fn c_form() -> Markup {
 	base_layout("BoH Crafting", html! {
 		form method="post" action="/crafting" {
-			input .textbox type="text" name="skill" id="skill" placeholder="Skill";
+			input .textbox type="text" name="skill" id="skill" placeholder="Skill" data-suggest="skill";
 			input type="submit" value="Go";
 		}
 	})
@@ -115,62 +115,58 @@ pub async fn c_form() -> Markup {
 
 #[derive(Deserialize, Debug)]
 pub struct CInput {
-	skill: String,
+	pub skill: String,
 }
 
 pub async fn crafting(
 	State(state): State<AppState>,
 	Form(input): Form<CInput>,
 ) -> Markup {
-	let save = state.save.lock().expect("Lock poison error");
-	let known_recipes: HashSet<_> = state.data.recipes.0.iter()
-		.chain(state.data.recipes.1.iter())
-		.chain(state.data.recipes.2.iter())
-		.filter(|r| save.skills.contains(&r.skill))
-		.map(|r| r.label.clone()).collect();
-	drop(save);
+	let skills = matching_skills(&state.data, &input.skill);
+	if skills.len() > 1 {
+		return base_layout("BoH Crafting", ui::choose_skill(&input.skill, &skills));
+	}
+	let report = CraftingReport::new(&input.skill, &state.data, &state.save.lock().expect("Lock poison error"));
+	drop(state.save);
+	match report {
+		Ok(r) => base_layout("BoH Recipes", ui::crafting(&r, &state.data)),
+		Err(e) => base_layout("BoH Crafting", html!{ (e) }),
+	}
+}
 
-	let skill = match state.data.skills.iter()
-		.find(|(_, s)| s.label.to_lowercase().starts_with(&input.skill.to_lowercase()))
-	{
-		Some(s) => s,
-		None => { return base_layout("BoH Crafting", html!{ (format!("Skill not found: {}", input.skill))}); },
-	};
-
-
-	let mut res = format!("<h2>Using skill {}</h2>", skill.1.label).to_owned();
-	let mut collate_recipes = |recipes: &Vec<Recipe>, level: RecipeLevel| {
-		res.push_str(&format!("<h3>{} recipes:</h3>", level));
-		for r in recipes.iter().filter(|r| skill.0 == &r.skill) {
-			let item = match level {
-				RecipeLevel::Prentice => String::new(),
-				RecipeLevel::Scholar => String::from(" using ") + &r.ingredient.as_ref().unwrap(),
-				RecipeLevel::Keeper => {
-					if let Some(item) = state.data.items.get(r.ingredient.as_ref().unwrap()) {
-						String::from(" using ") + &item.label
-					} else {
-						String::from(" using ") + &r.ingredient.as_ref().unwrap()
-					}
-				},
-			};
-			res.push_str(&match known_recipes.get(&r.label) {
-				Some(_) => format!("<p>{}{} ({})</p>", r.label, item, r.principle),
-				None    => format!("<p>{}{} ({}) [New Recipe!]</p>", r.label, item, r.principle),
-			});
+pub async fn pl_form() -> Markup {
+	base_layout("BoH Planner", html! {
+		form method="post" action="/plan" {
+			input .textbox type="text" name="goal" id="goal" placeholder="Item, memory or aspect" data-suggest="goal";
+			input type="submit" value="Go";
 		}
-	};
+	})
+}
 
-	collate_recipes(&state.data.recipes.0, RecipeLevel::Prentice);
-	collate_recipes(&state.data.recipes.1, RecipeLevel::Scholar);
-	collate_recipes(&state.data.recipes.2, RecipeLevel::Keeper);
+#[derive(Deserialize, Debug)]
+pub struct PlInput {
+	pub goal: String,
+}
 
-	base_layout("BoH Recipes", PreEscaped(res))
+pub async fn planner(
+	State(state): State<AppState>,
+	Form(input): Form<PlInput>,
+) -> Markup {
+	let report = PlanReport::new(&input.goal, &state.data, &state.save.lock().unwrap());
+	drop(state.save);
+	match report {
+		Ok(r) => base_layout("BoH Planner", ui::plan(&r)),
+		Err(e) => base_layout("BoH Planner", html!{ (e) }),
+	}
 }
 
 pub async fn i_form() -> Markup {
 	base_layout("BoH Item Browser", html! {
 		form method="post" action="/items" {
-			input .textbox type="text" name="principles" id="principles" placeholder="lantern,tool";
+			input .textbox type="text" name="principles" id="principles" placeholder="lantern & tool & !fragile" data-suggest="aspect";
+			input .textbox type="text" name="sort" id="sort" placeholder="Sort by aspect" data-suggest="aspect";
+			label { input type="checkbox" name="owned" value="true"; "Only my items" }
+			label { input type="checkbox" name="variants" value="true"; "Every variant" }
 			input type="submit" value="Go";
 		}
 	})
@@ -178,26 +174,136 @@ pub async fn i_form() -> Markup {
 
 #[derive(Deserialize, Debug)]
 pub struct IInput {
-	principles: String,
+	/// Aspect query, e.g. `lantern & tool & !fragile`
+	pub principles: String,
+	/// Aspect to order results by, strongest first
+	#[serde(default)]
+	pub sort: String,
+	/// Only list items the player has
+	#[serde(default)]
+	pub owned: bool,
+	/// List each variant separately instead of once per name
+	#[serde(default)]
+	pub variants: bool,
 }
 
 pub async fn items(
 	State(state): State<AppState>,
 	Form(input): Form<IInput>,
 ) -> Markup {
-	let aspects: Vec<_> = input.principles.split(",").collect();
-	let found = find_aspected(&state.data.items, aspects.as_slice());
-
-	let mut res = String::new();
-	for (label, aspects) in found {
-		res.push_str(&format!("<h3>{label}</h3>"));
-		res.push_str("<p>");
-		for (aspect, intensity) in aspects {
-			if !aspect.starts_with("boost") {
-				res.push_str(&format!("{aspect}: {intensity}, "));
+	let save = state.save.lock().unwrap();
+	let found = ItemReport::find(&input.principles, &input.sort, input.owned, input.variants, &state.data, &save);
+	drop(save);
+	match found {
+		Ok(found) => base_layout("BoH Item Browser", ui::items(&found, &state.data)),
+		Err(e) => base_layout("BoH Item Browser", html!{ (e) }),
+	}
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SearchInput {
+	#[serde(default)]
+	pub q: String,
+}
+
+pub async fn search_page(
+	State(state): State<AppState>,
+	Query(input): Query<SearchInput>,
+) -> Markup {
+	let results = search(&state.data, &input.q, 50);
+	base_layout("BoH Search", ui::search_results(&input.q, &results))
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct WInput {
+	#[serde(default)]
+	pub principle: String,
+	/// Element of the Soul, with `wisdom` finding where it is upgraded
+	#[serde(default)]
+	pub soul: String,
+	#[serde(default)]
+	pub wisdom: String,
+	/// Only workstations in unlocked rooms
+	#[serde(default)]
+	pub unlocked: bool,
+}
+
+pub async fn workstations(
+	State(state): State<AppState>,
+	Query(input): Query<WInput>,
+) -> Markup {
+	let report = WorkstationsReport::new(&input.principle, &input.soul, &input.wisdom, input.unlocked, &state.data, &state.save.lock().unwrap());
+	base_layout("BoH Workstations", html! {
+		form method="get" action="/workstations" {
+			input .textbox type="text" name="principle" id="principle" value=(input.principle) placeholder="Principle" data-suggest="principle";
+			select .textbox name="soul" id="soul" {
+				option value="" { "Any Element of the Soul" }
+				@for soul in souls() {
+					option value=(soul) selected[input.soul == soul] { (principles_from_soul(soul).0) }
+				}
 			}
+			input .textbox type="text" name="wisdom" id="wisdom" value=(input.wisdom) placeholder="Wisdom, e.g. birdsong";
+			label { input type="checkbox" name="unlocked" value="true" checked[input.unlocked]; "Only unlocked rooms" }
+			input type="submit" value="Go";
 		}
-		res.push_str("</p>");
-	}
-	base_layout("BoH Item Browser", PreEscaped(res))
+		@match &report {
+			Ok(r) => (ui::workstations(r, &state.data)),
+			Err(e) => (e),
+		}
+	})
+}
+
+pub async fn library(State(state): State<AppState>) -> Markup {
+	let report = LibraryReport::new(&state.data, &state.save.lock().unwrap());
+	base_layout("BoH Library", ui::library(&report, &state.data))
+}
+
+pub async fn visitors(State(state): State<AppState>) -> Markup {
+	let report = VisitorsReport::new(&state.data, &state.save.lock().unwrap());
+	base_layout("BoH Visitors", ui::visitors(&report))
+}
+
+pub async fn assistance(State(state): State<AppState>) -> Markup {
+	let report = AssistanceReport::new(&state.data, &state.save.lock().unwrap());
+	base_layout("BoH Assistance", ui::assistance(&report, &state.data))
+}
+
+fn not_found(what: &str) -> (StatusCode, Markup) {
+	(StatusCode::NOT_FOUND, base_layout("BoH Not Found", html! { (format!("{} not found", what)) }))
+}
+
+pub async fn item_page(
+	State(state): State<AppState>,
+	Path(id): Path<String>,
+) -> Result<Markup, (StatusCode, Markup)> {
+	let save = state.save.lock().unwrap();
+	let item = ItemDetail::new(&id, &state.data, &save).ok_or_else(|| not_found("Item"))?;
+	Ok(base_layout(&item.label, ui::item_detail(&item, &state.data)))
+}
+
+pub async fn book_page(
+	State(state): State<AppState>,
+	Path(id): Path<String>,
+) -> Result<Markup, (StatusCode, Markup)> {
+	let save = state.save.lock().unwrap();
+	let book = BookDetail::new(&id, &state.data, &save).ok_or_else(|| not_found("Book"))?;
+	Ok(base_layout(&book.label, ui::book_detail(&book, &state.data)))
+}
+
+pub async fn skill_page(
+	State(state): State<AppState>,
+	Path(id): Path<String>,
+) -> Result<Markup, (StatusCode, Markup)> {
+	let save = state.save.lock().unwrap();
+	let skill = SkillDetail::new(&id, &state.data, &save).ok_or_else(|| not_found("Skill"))?;
+	Ok(base_layout(&skill.label, ui::skill_detail(&skill, &state.data)))
+}
+
+pub async fn workstation_page(
+	State(state): State<AppState>,
+	Path(id): Path<String>,
+) -> Result<Markup, (StatusCode, Markup)> {
+	let save = state.save.lock().unwrap();
+	let station = WorkstationDetail::new(&id, &state.data, &save).ok_or_else(|| not_found("Workstation"))?;
+	Ok(base_layout(&station.station.label, ui::workstation_detail(&station, &state.data)))
 }
@@ -0,0 +1,64 @@
+use std::{
+	collections::hash_map::DefaultHasher,
+	hash::{Hash, Hasher},
+	path::{self, Component, PathBuf},
+};
+
+use axum::{
+	extract::{Path, State},
+	http::{header, HeaderMap, StatusCode},
+	response::{IntoResponse, Response},
+};
+
+/// Files under `assets/`, compiled into the binary
+const EMBEDDED: &[(&str, &[u8])] = &[
+	("main.css", include_bytes!("../assets/main.css")),
+	("suggest.js", include_bytes!("../assets/suggest.js")),
+];
+
+/// Serves an asset from the override directory if it has one, else from the binary
+pub async fn serve(
+	State(overrides): State<Option<PathBuf>>,
+	Path(file): Path<String>,
+	headers: HeaderMap,
+) -> Response {
+	// Only plain relative names, so no `..`, root or Windows drive can reach outside the directory
+	if file.is_empty() || !path::Path::new(&file).components().all(|c| matches!(c, Component::Normal(_))) {
+		return StatusCode::NOT_FOUND.into_response();
+	}
+	if let Some(dir) = overrides {
+		if let Ok(contents) = tokio::fs::read(dir.join(&file)).await {
+			return respond(&file, &contents, "no-cache", &headers);
+		}
+	}
+	match EMBEDDED.iter().find(|(name, _)| *name == file) {
+		Some((_, contents)) => respond(&file, contents, "public, max-age=3600", &headers),
+		None => StatusCode::NOT_FOUND.into_response(),
+	}
+}
+
+fn respond(file: &str, contents: &[u8], cache: &'static str, headers: &HeaderMap) -> Response {
+	let mut hasher = DefaultHasher::new();
+	contents.hash(&mut hasher);
+	let etag = format!("\"{:x}\"", hasher.finish());
+	let fresh = headers.get(header::IF_NONE_MATCH).and_then(|v| v.to_str().ok())
+		.is_some_and(|v| v.split(',').any(|t| t.trim() == etag));
+	let cache_headers = [(header::ETAG, etag), (header::CACHE_CONTROL, cache.to_string())];
+	if fresh {
+		(StatusCode::NOT_MODIFIED, cache_headers).into_response()
+	} else {
+		(cache_headers, [(header::CONTENT_TYPE, mime(file))], contents.to_vec()).into_response()
+	}
+}
+
+fn mime(file: &str) -> &'static str {
+	match file.rsplit('.').next() {
+		Some("css") => "text/css; charset=utf-8",
+		Some("js") => "text/javascript; charset=utf-8",
+		Some("svg") => "image/svg+xml",
+		Some("png") => "image/png",
+		Some("ico") => "image/x-icon",
+		Some("woff2") => "font/woff2",
+		_ => "application/octet-stream",
+	}
+}
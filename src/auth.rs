@@ -0,0 +1,110 @@
+use axum::{
+	extract::{Request, State},
+	http::{header, HeaderMap, HeaderValue, StatusCode},
+	middleware::Next,
+	response::{IntoResponse, Response},
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, NON_ALPHANUMERIC};
+
+/// Cookie remembering a token given in the query string
+const TOKEN_COOKIE: &str = "boh_token";
+
+/// Who may use the server
+#[derive(Clone, Debug)]
+pub enum Access {
+	Open,
+	/// A shared secret, given as `Authorization: Bearer <token>`, a `?token=` query or a cookie
+	Token(String),
+	/// HTTP basic auth with `user:password`
+	Basic(String),
+}
+
+impl Access {
+	pub fn is_open(&self) -> bool {
+		matches!(self, Access::Open)
+	}
+}
+
+pub async fn check(State(access): State<Access>, req: Request, next: Next) -> Response {
+	match &access {
+		Access::Open => next.run(req).await,
+		Access::Token(token) => {
+			let headers = req.headers();
+			let query_token = req.uri().query().and_then(|q| q.split('&')
+				.find_map(|p| p.strip_prefix("token=")))
+				.is_some_and(|t| same(&decode(&t.replace('+', " ")), token.as_bytes()));
+			let authorised = query_token
+				|| bearer(headers).is_some_and(|t| same(t.as_bytes(), token.as_bytes()))
+				|| cookie(headers, TOKEN_COOKIE).is_some_and(|t| same(&decode(t), token.as_bytes()));
+			if !authorised {
+				return (StatusCode::UNAUTHORIZED, "Access token required").into_response();
+			}
+			let mut res = next.run(req).await;
+			if query_token {
+				let value = utf8_percent_encode(token, NON_ALPHANUMERIC);
+				let cookie = format!("{}={}; Path=/; HttpOnly; SameSite=Strict", TOKEN_COOKIE, value);
+				if let Ok(cookie) = HeaderValue::from_str(&cookie) {
+					res.headers_mut().insert(header::SET_COOKIE, cookie);
+				}
+			}
+			res
+		},
+		Access::Basic(credentials) => {
+			let given = req.headers().get(header::AUTHORIZATION)
+				.and_then(|v| v.to_str().ok())
+				.and_then(|v| v.strip_prefix("Basic "))
+				.and_then(|v| STANDARD.decode(v.trim()).ok());
+			if given.is_some_and(|g| same(&g, credentials.as_bytes())) {
+				next.run(req).await
+			} else {
+				(
+					StatusCode::UNAUTHORIZED,
+					[(header::WWW_AUTHENTICATE, "Basic realm=\"BoHelper\"")],
+					"Login required",
+				).into_response()
+			}
+		},
+	}
+}
+
+fn bearer(headers: &HeaderMap) -> Option<&str> {
+	headers.get(header::AUTHORIZATION)?.to_str().ok()?.strip_prefix("Bearer ")
+}
+
+fn cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+	headers.get_all(header::COOKIE).iter()
+		.filter_map(|v| v.to_str().ok())
+		.flat_map(|v| v.split(';'))
+		.find_map(|c| c.trim().strip_prefix(name)?.strip_prefix('='))
+}
+
+fn decode(value: &str) -> Vec<u8> {
+	percent_decode_str(value).collect()
+}
+
+/// Compares secrets in time independent of where they differ
+fn same(given: &[u8], secret: &[u8]) -> bool {
+	given.len() == secret.len() && given.iter().zip(secret).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn same_compares_whole_secrets() {
+		assert!(same(b"secret", b"secret"));
+		assert!(same(b"", b""));
+		assert!(!same(b"secreT", b"secret"));
+		assert!(!same(b"secre", b"secret"));
+		assert!(!same(b"secrets", b"secret"));
+		assert!(!same(b"", b"secret"));
+	}
+
+	#[test]
+	fn decode_percent_escapes() {
+		assert_eq!(decode("a%2Bb%20c"), b"a+b c");
+		assert_eq!(decode("plain"), b"plain");
+	}
+}
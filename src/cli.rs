@@ -0,0 +1,165 @@
+use serde::Serialize;
+
+use crate::data::Data;
+use crate::planner::Step;
+use crate::report::*;
+use crate::save::SaveData;
+use crate::solver::Target;
+use crate::ui::{dis_principles, dis_set, dis_vec, dis_weather};
+
+pub const COMMANDS: [&str; 7] = ["mems", "solve", "slots", "craft", "items", "plan", "tui"];
+
+pub const USAGE: &str = "Usage: bo_helper [game path] <command> [--json]
+Without a command, serves the web UI (options: --host, --port, --token or --auth user:password,
+--assets <dir> to override the built-in stylesheet and other assets).
+Commands:
+	mems <principles>              Memories from your items, e.g. mems lantern>=5, moth:2
+	solve <principle> <principle>  Skills and memories for two principles
+	slots <target> [workstation]   Ways to fill a workstation (by ID) for a principle, recipe or book
+	craft <skill>                  Recipes of a skill and where to craft them
+	items <query>                  Items matching an aspect query, e.g. items 'lantern & tool & !fragile'
+	      [--owned] [--variants]   (only your items, every variant separately)
+	      [--sort <aspect>]        (strongest in an aspect first)
+	plan <goal>                    Crafting plan for an item, memory or aspect
+	tui                            Interactive terminal UI
+The game path may instead be given in BOH_GAME_PATH.";
+
+/// Runs a command, printing a table or JSON to stdout. Returns false on bad usage or no results.
+pub fn run(args: &[String], data: &Data, save: &SaveData) -> bool {
+	let json = args.iter().any(|a| a == "--json");
+	let args: Vec<_> = args.iter().filter(|a| *a != "--json").map(|a| a.as_str()).collect();
+	let res = match args.as_slice() {
+		["mems", principles @ ..] if !principles.is_empty() => {
+			let report = MemoryReport::new(&principles.join(" "), data, save);
+			output(json, &report, !report.memories.is_empty() || !report.craftable.is_empty(), || {
+				if !report.now.is_empty() {
+					println!("Now: {}", report.now.join(", "));
+				}
+				for mem in &report.memories {
+					println!("{} ({}){}\n\t{}", mem.label, dis_principles(&mem.aspects, data), dis_weather(&mem.weather), dis_set(&mem.sources));
+				}
+				for mem in &report.craftable {
+					println!("{} ({}){}\n\tcraft {}", mem.label, dis_principles(&mem.aspects, data), dis_weather(&mem.weather), dis_set(&mem.sources));
+				}
+			})
+		},
+		["solve", p1, p2] => {
+			let report = SolveReport::new(p1, p2, data, save);
+			output(json, &report, !report.skills.is_empty() || !report.memories.is_empty(), || {
+				for skill in &report.skills {
+					println!("{}", skill.skill);
+					for c in &skill.commitments {
+						match c.workstations.len() {
+							0 => println!("\t{}: {} can't be upgraded", c.wisdom, c.soul),
+							_ => println!("\t{}: {} at {}", c.wisdom, c.soul, dis_vec(&c.workstations)),
+						}
+					}
+				}
+				for mem in &report.memories {
+					println!("{} ({}){}\n\t{}", mem.label, dis_principles(&mem.aspects, data), dis_weather(&mem.weather), dis_set(&mem.sources));
+				}
+			})
+		},
+		["slots", target, workstation @ ..] => {
+			SlotReport::new(&workstation.join(" "), target, data, save).and_then(|report| output(json, &report, report.workstations.iter().any(|s| !s.is_empty()), || {
+				match &report.target {
+					Target::Principle(q) => println!("Reaching {} {}", data.aspect_label(&q.aspect), q.min),
+					Target::Recipe(r) => println!("Crafting {} ({} {})", r.label, data.aspect_label(&r.principle), r.amount),
+					Target::Lesson { label, principle, amount, .. } => println!("Studying {} ({} {})", label, data.aspect_label(principle), amount),
+				}
+				if !report.now.is_empty() {
+					println!("Now: {}", report.now.join(", "));
+				}
+				for station in report.workstations.iter().filter(|s| !s.is_empty()) {
+					println!("{}", station[0].workstation);
+					for s in station {
+						let cards: Vec<_> = s.cards().map(|c| format!("{}{}", c.label, dis_weather(&c.weather))).collect();
+						println!("\t{} {}\t{}", s.total, data.aspect_label(report.target.principle()), cards.join(" + "));
+					}
+				}
+			}))
+		},
+		["craft", skill @ ..] if !skill.is_empty() => {
+			CraftingReport::new(&skill.join(" "), data, save).and_then(|report| output(json, &report, !report.recipes.is_empty(), || {
+				for r in &report.recipes {
+					let stations: Vec<_> = r.workstations.iter().map(|w| w.label.clone()).collect();
+					let makes: Vec<_> = r.makes.iter().map(|m| format!("{}x {}", m.quantity, m.label)).collect();
+					println!("{}\t{}\t{} {}\t{}\t{}{}{}",
+						r.level, r.label, data.aspect_label(&r.principle), r.amount,
+						r.ingredient.as_deref().unwrap_or("-"),
+						makes.join(", "),
+						if r.known { String::new() } else { String::from("\t[New Recipe!]") },
+						dis_weather(&r.weather));
+					if !stations.is_empty() {
+						println!("\t\tat {}", dis_vec(&stations));
+					}
+				}
+			}))
+		},
+		["items", query @ ..] if !query.is_empty() => {
+			let mut query = query.to_vec();
+			let owned = take_switch(&mut query, "--owned");
+			let variants = take_switch(&mut query, "--variants");
+			let sort = match query.iter().position(|a| *a == "--sort") {
+				Some(i) if i + 1 < query.len() => query.drain(i..i + 2).nth(1).unwrap(),
+				_ => "",
+			};
+			ItemReport::find(&query.join(" "), sort, owned, variants, data, save).and_then(|found| output(json, &found, !found.is_empty(), || {
+				for item in &found {
+					println!("{}{}\t{}", item.label, dis_weather(&item.weather), dis_principles(&item.aspects, data));
+					for v in &item.variants {
+						println!("\t{}{}\t{}", v.label, dis_weather(&v.weather), dis_principles(&v.aspects, data));
+					}
+				}
+			}))
+		},
+		["plan", goal @ ..] if !goal.is_empty() => {
+			PlanReport::new(&goal.join(" "), data, save).and_then(|report| output(json, &report, report.steps.is_some(), || {
+				match &report.steps {
+					None => println!("No plan found for {}", report.label),
+					Some(steps) => for (i, step) in steps.iter().enumerate() {
+						match step {
+							Step::Have { item } => println!("{}. Take {}", i + 1, item),
+							Step::Recall { item, memory } => println!("{}. Scrutinise or read {} to remember {}", i + 1, item, memory),
+							Step::Craft { recipe, skill, workstations, ingredient, output } => println!(
+								"{}. Craft {} ({}) with {}{} at {}", i + 1, output, recipe, skill,
+								ingredient.as_ref().map(|i| format!(" using {}", i)).unwrap_or_default(),
+								dis_vec(workstations)),
+						}
+					},
+				}
+			}))
+		},
+		_ => {
+			eprintln!("{}", USAGE);
+			return false;
+		},
+	};
+	match res {
+		Ok(()) => true,
+		Err(e) => {
+			eprintln!("{}", e);
+			false
+		},
+	}
+}
+
+/// Removes a flag from the arguments, returning whether it was there
+fn take_switch(args: &mut Vec<&str>, flag: &str) -> bool {
+	let len = args.len();
+	args.retain(|a| *a != flag);
+	args.len() != len
+}
+
+/// Prints the report, failing afterwards if nothing was found
+fn output<T: Serialize>(json: bool, report: &T, found: bool, table: impl FnOnce()) -> Result<(), QueryError> {
+	if json {
+		println!("{}", serde_json::to_string_pretty(report).expect("Failed to serialise result"));
+	} else {
+		table();
+	}
+	match found {
+		true => Ok(()),
+		false => Err(QueryError::NotFound(String::from("No results"))),
+	}
+}
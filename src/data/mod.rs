@@ -2,7 +2,7 @@
 
 use std::{collections::HashMap, fmt};
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 mod read;
 pub use read::{init_items, Data};
@@ -31,6 +31,16 @@ pub struct Item {
 	pub aspects: AspectMap,
 	pub scrutiny: Option<String>,
 	pub fatigues: ExhaustType,
+	/// ID of the item's family in `Data::families`
+	pub family: String,
+}
+
+/// Items which are variants of the same thing, partly guessed from their labels
+#[derive(Serialize, Clone, Debug)]
+pub struct ItemFamily {
+	pub label: String,
+	/// Item IDs, sorted
+	pub members: Vec<String>,
 }
 
 #[allow(dead_code)]
@@ -40,11 +50,79 @@ pub struct Book {
 	pub aspects: AspectMap,
 	pub skill: (String, isize),
 	pub memory: String,
+	/// Language the book is written in, if it needs one to be read
+	pub language: Option<String>,
+}
+
+impl Book {
+	/// The principle and amount needed to master the book, from its `mystery.*` aspect
+	pub fn mystery(&self) -> Option<(String, isize)> {
+		self.aspects.iter()
+			.find_map(|(a, i)| a.strip_prefix("mystery.").map(|p| (p.to_string(), *i)))
+	}
+}
+
+/// An aspect as defined by the game, for display
+#[derive(Serialize, Clone, Debug)]
+pub struct Aspect {
+	pub label: String,
+	pub description: String,
+	/// Internal aspects the game doesn't show the player
+	pub hidden: bool,
+}
+
+/// A season or kind of weather
+#[derive(Serialize, Clone, Debug)]
+pub struct Weather {
+	pub label: String,
+	pub aspects: AspectMap,
 }
 
+/// Hired help, which can fill slots accepting assistance
+#[derive(Serialize, Clone, Debug)]
+pub struct Assistant {
+	pub label: String,
+	pub aspects: AspectMap,
+}
+
+/// A verb other than a workstation, such as a task in a room
+#[derive(Serialize, Clone, Debug)]
+pub struct Verb {
+	pub id: String,
+	pub label: String,
+	pub slots: Vec<Slot>,
+}
+
+/// A visitor to Brancrug or the house
+#[derive(Serialize, Clone, Debug)]
+pub struct Visitor {
+	pub label: String,
+	pub aspects: AspectMap,
+	pub offers: Vec<VisitorOffer>,
+}
+
+/// Something a visitor will do, from a recipe requiring them
+#[derive(Serialize, Clone, Debug)]
+pub struct VisitorOffer {
+	pub label: String,
+	/// Other requirements of the recipe, such as a principle or an item
+	pub wants: Vec<(String, isize)>,
+	/// Element IDs the recipe produces: memories, lessons, languages or items
+	pub gives: Vec<String>,
+}
+
+/// An Element of the Soul
 #[derive(Clone, Debug)]
+pub struct Ability {
+	pub label: String,
+	pub aspects: AspectMap,
+}
+
+#[derive(Serialize, Clone, Debug)]
 pub struct Skill {
+	pub id: String,
 	pub label: String,
+	pub aspects: AspectMap,
 	pub principles: (String, String),
 	pub wisdoms: ((String, String), (String, String)),
 }
@@ -55,19 +133,68 @@ impl Skill {
 	}
 }
 
-#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
 pub enum SpecialWorkstation {
 	Kitchen,
 	Instrument,
 	None,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+/// What kind of card a slot takes
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SlotKind {
+	Skill,
+	/// An Element of the Soul
+	Soul,
+	Memory,
+	Item,
+}
+
+/// A card slot on a workstation
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Slot {
+	pub id: String,
+	pub label: String,
+	pub kind: SlotKind,
+	/// A card needs at least one of these, if there are any
+	pub required: AspectMap,
+	/// A card needs all of these
+	pub essential: AspectMap,
+	/// A card can't have any of these
+	pub forbidden: AspectMap,
+}
+
+impl Slot {
+	pub fn new(id: String, label: String, required: AspectMap, essential: AspectMap, forbidden: AspectMap) -> Self {
+		let kind = if required.contains_key("skill") || essential.contains_key("skill") {
+			SlotKind::Skill
+		} else if required.contains_key("ability") || essential.contains_key("ability") {
+			SlotKind::Soul
+		} else if required.contains_key("memory") || essential.contains_key("memory") {
+			SlotKind::Memory
+		} else {
+			SlotKind::Item
+		};
+		Slot { id, label, kind, required, essential, forbidden }
+	}
+
+	/// Whether a card with these aspects fits the slot
+	pub fn accepts(&self, aspects: &AspectMap) -> bool {
+		let has = |(a, i): (&String, &isize)| aspects.get(a).is_some_and(|v| v >= i);
+		(self.required.is_empty() || self.required.iter().any(has)) &&
+			self.essential.iter().all(has) &&
+			!self.forbidden.keys().any(|a| aspects.contains_key(a))
+	}
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Workstation {
+	/// Verb ID
+	pub id: String,
 	pub label: String,
 	pub principles: Vec<String>,
-	pub subject: Vec<String>,
-	pub with: Vec<String>,
+	pub slots: Vec<Slot>,
 	pub wisdoms: Vec<String>,
 	pub special: SpecialWorkstation,
 }
@@ -81,35 +208,52 @@ impl Workstation {
 			} else {
 				self.accepts_aspect(ingredient)
 			}
-		} else { true }) && self.accepts_principles(&[&principles.0, &principles.1])
+		} else { true })
+			&& self.principles.contains(&recipe.principle)
+			&& self.accepts_principles(&[&principles.0, &principles.1])
 	}
 
 	pub fn accepts_principles(&self, principles: &[&str]) -> bool {
 		self.principles.iter().any(|p| principles.contains(&p.as_str()))
 	}
 
+	/// Slots taking items and books, rather than skills, souls or memories
+	pub fn item_slots(&self) -> impl Iterator<Item = &Slot> {
+		self.slots.iter().filter(|s| s.kind == SlotKind::Item)
+	}
+
+	/// Whether an item or book with these aspects fits any of the item slots
+	pub fn accepts(&self, aspects: &AspectMap) -> bool {
+		self.item_slots().any(|s| s.accepts(aspects))
+	}
+
 	fn accepts_aspect(&self, aspect: &String) -> bool {
 		match aspect.as_str() {
 			"instrument" => self.special == SpecialWorkstation::Instrument,
 			"kitchenware" | "knife" | "egg" => self.special == SpecialWorkstation::Kitchen,
-			_ => self.subject.iter().chain(self.with.iter()).any(|a| a == aspect)
+			_ => self.item_slots().any(|s| s.required.contains_key(aspect) || s.essential.contains_key(aspect)),
 		}
 	}
 
 	fn accepts_item(&self, item: &Item) -> bool {
-		item.aspects.keys().any(|a| self.accepts_aspect(a))
+		self.accepts(&item.aspects)
 	}
 }
 
-#[derive(Clone, Debug)]
+#[derive(Serialize, Clone, Debug)]
 pub struct Recipe {
 	pub label:      String,
 	pub skill:      String,
 	pub principle:  String,
+	pub amount:     isize,
 	pub ingredient: Option<String>,
+	/// Produced element IDs and quantities
+	pub effects:    Vec<(String, isize)>,
+	/// Seasons, weather or their aspects the recipe requires
+	pub weather:    Vec<String>,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum RecipeLevel {
 	Prentice,
 	Keeper,
@@ -125,6 +269,11 @@ impl fmt::Display for RecipeLevel {
 	}
 }
 
+/// IDs of the Elements of the Soul
+pub fn souls() -> Vec<&'static str> {
+	vec!["xcho", "xere", "xfet", "xhea", "xmet", "xpho", "xsha", "xtri", "xwis"]
+}
+
 pub fn principles_from_soul(soul: &str) -> (&'static str, Vec<&'static str>) {
 	match soul {
 		"xcho" => ("Chor", vec!["heart", "grail"]),
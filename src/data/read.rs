@@ -1,11 +1,11 @@
 use std::{
-	collections::HashMap,
-	io::{BufReader, Read},
-	fs::File,
-	path::PathBuf,
+	collections::{HashMap, HashSet},
+	io::BufReader,
+	fs::{self, File},
+	path::Path,
 };
 
-use serde::Deserialize;
+use serde::{de::DeserializeOwned, Deserialize};
 
 use super::*;
 
@@ -26,6 +26,33 @@ struct ProtoTrigger {
 	fatiguing: Option<String>,
 }
 
+/// Any element, as found in every file under `elements`
+#[derive(Deserialize, Clone, Debug)]
+struct ElementFile {
+	#[serde(default)]
+	elements: Vec<SerdeElement>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+struct SerdeElement {
+	#[serde(alias = "ID", alias = "Id", default)]
+	id: String,
+	#[serde(alias = "Label", default)]
+	label: String,
+	#[serde(default)]
+	aspects: AspectMap,
+	#[serde(default)]
+	inherits: String,
+	#[serde(alias = "isAspect", alias = "IsAspect", default)]
+	is_aspect: bool,
+	#[serde(alias = "isHidden", alias = "IsHidden", default)]
+	is_hidden: bool,
+	#[serde(alias = "Desc", alias = "description", default)]
+	desc: String,
+	#[serde(default)]
+	xtriggers: HashMap<String, serde_json::Value>,
+}
+
 #[derive(Deserialize, Clone, Debug)]
 struct ItemFile {
 	elements: Vec<SerdeItem>
@@ -74,63 +101,67 @@ struct Trigger {
 	level: isize,
 }
 
+/// Any verb file; verbs with a skill slot and principles are workstations
 #[derive(Deserialize, Clone, Debug)]
 struct WorkstationFile {
+	#[serde(default)]
 	verbs: Vec<SerdeWorkstation>,
 }
 
-impl WorkstationFile {
-	fn into_workstations(self) -> Vec<Workstation> {
-		let mut workstations = Vec::new();
-		for mut station in self.verbs {
-			let with = station.slots.remove(4);
-			let subject = station.slots.remove(3);
-			let special = if station.aspects.contains_key("instrument") {
-				SpecialWorkstation::Instrument
-			} else {
-				SpecialWorkstation::None
-			};
-			workstations.push(Workstation {
-				label: station.label,
-				principles: station.hints,
-				subject: subject.required.into_keys().collect(),
-				with: with.required.into_keys().collect(),
-				special,
-				wisdoms: station.aspects.into_iter().filter(|a| a.0.starts_with("e.")).map(|a| a.0).collect(),
-			});
+impl SerdeWorkstation {
+	/// A workstation if the verb has a skill slot and principles, else a plain verb
+	fn into_verb(self) -> Result<Workstation, Verb> {
+		let slots: Vec<_> = self.slots.into_iter()
+			.map(|s| Slot::new(s.id, s.label, s.required, s.essential, s.forbidden))
+			.collect();
+		if self.hints.is_empty() || !slots.iter().any(|s| s.kind == SlotKind::Skill) {
+			return Err(Verb { id: self.id, label: self.label, slots });
 		}
-		let kitchen_aspects = vec!["sustenance".to_string(),"beverage".to_string(),"root".to_string(),"flower".to_string(),"leaf".to_string(),"fuel".to_string()];
-		let mut kitchen = Workstation {
-			label: String::from("Kitchen Range: Gaol"),
-			principles: vec!["scale".to_string(),"lantern".to_string(),"nectar".to_string(),"grail".to_string()],
-			subject: kitchen_aspects.clone(),
-			with: kitchen_aspects,
-			special: SpecialWorkstation::Kitchen,
-			wisdoms: Vec::new(),
+		// Kitchens take food and drink; their tools are checked separately in `accepts_aspect`
+		let special = if self.aspects.contains_key("instrument") {
+			SpecialWorkstation::Instrument
+		} else if slots.iter().any(|s| s.kind == SlotKind::Item && (s.required.contains_key("sustenance") || s.required.contains_key("beverage"))) {
+			SpecialWorkstation::Kitchen
+		} else {
+			SpecialWorkstation::None
 		};
-		workstations.push(kitchen.clone());
-		kitchen.label = String::from("Hearth: Hall of Voices");
-		kitchen.principles = vec!["moon".to_string(),"edge".to_string(),"nectar".to_string(),"grail".to_string()];
-		workstations.push(kitchen.clone());
-		kitchen.label = String::from("Kitchen Range: Servants");
-		kitchen.principles = vec!["scale".to_string(),"heart".to_string(),"nectar".to_string(),"grail".to_string()];
-		workstations.push(kitchen);
-
-		workstations
+		Ok(Workstation {
+			id: self.id,
+			label: self.label,
+			principles: self.hints,
+			slots,
+			special,
+			wisdoms: self.aspects.into_keys().filter(|a| a.starts_with("e.")).collect(),
+		})
 	}
 }
 
 #[derive(Deserialize, Clone, Debug)]
 struct SerdeWorkstation {
+	#[serde(alias = "ID", default)]
+	id: String,
+	#[serde(alias = "Label", default)]
 	label: String,
-	slots: Vec<Slot>,
+	#[serde(default)]
+	slots: Vec<SerdeSlot>,
+	#[serde(default)]
 	aspects: AspectMap,
+	#[serde(default)]
 	hints: Vec<String>,
 }
 
 #[derive(Deserialize, Clone, Debug)]
-struct Slot {
+struct SerdeSlot {
+	#[serde(default)]
+	id: String,
+	#[serde(alias = "Label", default)]
+	label: String,
+	#[serde(default)]
 	required: AspectMap,
+	#[serde(default)]
+	essential: AspectMap,
+	#[serde(default)]
+	forbidden: AspectMap,
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -162,48 +193,134 @@ struct RecipeFile {
 	recipes: Vec<SerdeRecipe>,
 }
 
+/// Any recipe file, read leniently since only some recipes are of interest
+#[derive(Deserialize, Clone, Debug)]
+struct AnyRecipeFile {
+	#[serde(default)]
+	recipes: Vec<AnyRecipe>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+struct AnyRecipe {
+	#[serde(alias = "ID", default)]
+	id: String,
+	#[serde(alias = "Label", default)]
+	label: String,
+	#[serde(default)]
+	reqs: HashMap<String, serde_json::Value>,
+	#[serde(default)]
+	effects: HashMap<String, serde_json::Value>,
+}
+
 #[derive(Deserialize, Clone, Debug)]
 struct SerdeRecipe {
 	#[serde(rename = "Label")]
 	label: String,
 	reqs: AspectMap,
+	#[serde(default)]
+	effects: HashMap<String, serde_json::Value>,
 }
 
 impl SerdeRecipe {
-	fn into_recipe(self) -> Recipe {
+	fn into_recipe(self, weather: &HashMap<String, Weather>) -> Recipe {
 		let mut skill = None;
 		let mut principle = None;
 		let mut ingredient = None;
-		for (k, _) in self.reqs {
+		let mut conditions = Vec::new();
+		for (k, v) in self.reqs {
 			if k == "ability" { continue }
-			else if k.starts_with("s.") {
+			else if is_weather(&k, weather) {
+				conditions.push(k);
+			} else if k.starts_with("s.") {
 				skill = Some(k);
 			} else if principles().contains(&k.as_str()) {
-				principle = Some(k);
+				principle = Some((k, v));
 			} else {
 				ingredient = Some(k);
 			}
 		}
+		let (principle, amount) = principle.expect("Recipe: No principle found");
+		// Effects may be quantities or expressions; only positive quantities produce elements
+		let mut effects: Vec<_> = self.effects.into_iter().filter_map(|(id, qty)| {
+			let qty = quantity(&qty)?;
+			(qty > 0).then_some((id, qty as isize))
+		}).collect();
+		effects.sort();
 		Recipe {
 			label: self.label,
 			skill: skill.expect("Recipe: No skill found"),
-			principle: principle.expect("Recipe: No principle found"),
+			principle,
+			amount,
 			ingredient,
+			effects,
+			weather: conditions,
 		}
 	}
 }
 
+/// Whether an aspect or element ID is a season or weather, or an aspect of one
+fn is_weather(id: &str, weather: &HashMap<String, Weather>) -> bool {
+	!principles().contains(&id) &&
+		(weather.contains_key(id) || weather.values().any(|w| w.aspects.contains_key(id)))
+}
+
+/// A recipe requirement or effect quantity, which may be written as a number or a string.
+/// Expressions aren't evaluated.
+fn quantity(value: &serde_json::Value) -> Option<i64> {
+	value.as_i64().or_else(|| value.as_str().and_then(|q| q.parse().ok()))
+}
+
 #[derive(Clone, Debug)]
 pub struct Data {
 	pub items:  HashMap<String, Item>,
 	pub books:  HashMap<String, Book>,
 	pub skills: HashMap<String, Skill>,
+	pub abilities: HashMap<String, Ability>,
+	pub aspects: HashMap<String, Aspect>,
+	/// Language element IDs and labels
+	pub languages: HashMap<String, String>,
+	pub visitors: HashMap<String, Visitor>,
+	pub assistance: HashMap<String, Assistant>,
+	/// Seasons and weather
+	pub weather: HashMap<String, Weather>,
+	/// Element IDs with the seasons, weather or their aspects which trigger changes in them
+	pub weather_triggers: HashMap<String, Vec<String>>,
+	pub families: HashMap<String, ItemFamily>,
+	/// Labels of every element, including rooms
+	pub element_labels: HashMap<String, String>,
 	pub workstations: Vec<Workstation>,
+	/// Verbs with slots which aren't workstations
+	pub verbs: Vec<Verb>,
 	pub recipes: (Vec<Recipe>, Vec<Recipe>, Vec<Recipe>),
 }
 
-pub fn init_items(data_path: &PathBuf) -> Data {
-	let prototypes_rdr = open_data(data_path.clone(), "elements", "_prototypes.json");
+impl Data {
+	pub fn all_recipes(&self) -> impl Iterator<Item = &Recipe> + Clone {
+		self.recipes.0.iter().chain(self.recipes.1.iter()).chain(self.recipes.2.iter())
+	}
+
+	/// Label of an aspect, falling back to its ID
+	pub fn aspect_label<'a>(&'a self, id: &'a str) -> &'a str {
+		self.aspects.get(id).map(|a| a.label.as_str()).unwrap_or(id)
+	}
+
+	/// Whether an aspect is internal to the game and shouldn't be displayed
+	pub fn aspect_hidden(&self, id: &str) -> bool {
+		self.aspects.get(id).is_some_and(|a| a.hidden)
+	}
+
+	/// Label of an item, book, skill or other element ID such as a room, falling back to the ID itself
+	pub fn label<'a>(&'a self, id: &'a str) -> &'a str {
+		self.items.get(id).map(|i| i.label.as_str())
+			.or_else(|| self.books.get(id).map(|b| b.label.as_str()))
+			.or_else(|| self.skills.get(id).map(|s| s.label.as_str()))
+			.or_else(|| self.element_labels.get(id).map(|l| l.as_str()))
+			.unwrap_or(id)
+	}
+}
+
+pub fn init_items(data_path: &Path) -> Data {
+	let prototypes_rdr = open_data(data_path, "elements", "_prototypes.json");
 	let prototypes_json: PrototypeFile = serde_json::from_reader(prototypes_rdr).expect("Failed to parse prototypes file");
 
 	let mut prototypes = HashMap::new();
@@ -214,49 +331,77 @@ pub fn init_items(data_path: &PathBuf) -> Data {
 		}
 	}
 
-	let items_rdr = open_data(data_path.clone(), "elements", "aspecteditems.json");
+	let elements = read_elements(data_path);
+	let abilities = parse_abilities(&elements, &prototypes);
+	let aspects = parse_aspects(&elements);
+	let languages: HashMap<_, _> = elements.iter().filter(|e| e.aspects.contains_key("language"))
+		.map(|e| (e.id.clone(), e.label.clone())).collect();
+	let visitors = read_visitors(data_path, &elements);
+	let assistance = elements.iter().filter(|e| e.aspects.contains_key("assistance"))
+		.map(|e| (e.id.clone(), Assistant { label: e.label.clone(), aspects: e.aspects.clone() }))
+		.collect();
+	let weather: HashMap<_, _> = elements.iter().filter(|e| e.aspects.contains_key("season") || e.aspects.contains_key("weather"))
+		.map(|e| (e.id.clone(), Weather { label: e.label.clone(), aspects: e.aspects.clone() }))
+		.collect();
+	let weather_triggers = elements.iter().filter_map(|e| {
+		let mut triggers: Vec<_> = e.xtriggers.keys().filter(|k| is_weather(k, &weather)).cloned().collect();
+		triggers.sort();
+		(!triggers.is_empty()).then(|| (e.id.clone(), triggers))
+	}).collect();
+	let element_labels = elements.iter().filter(|e| !e.label.is_empty()).map(|e| (e.id.clone(), e.label.clone())).collect();
+
+	let items_rdr = open_data(data_path, "elements", "aspecteditems.json");
 	let items_json: ItemFile = serde_json::from_reader(items_rdr).expect("Failed to parse items file");
-	let items = parse_items(items_json, prototypes);
+	let mut items = parse_items(items_json, &prototypes);
+	let families = group_families(&mut items);
 
-	let books_rdr = open_data(data_path.clone(), "elements", "tomes.json");
+	let books_rdr = open_data(data_path, "elements", "tomes.json");
 	let books_json: BookFile = serde_json::from_reader(books_rdr).expect("Failed to parse tomes file");
-	let books = parse_books(books_json);
+	let books = parse_books(books_json, &languages);
 
-	let workstations_rdr = open_data(data_path.clone(), "verbs", "workstations_library_world.json");
-	let workstations_json: WorkstationFile = serde_json::from_reader(workstations_rdr).expect("Failed to parse workstations file");
-	let workstations = workstations_json.into_workstations();
+	let (workstations, verbs) = read_verbs(data_path);
 
-	let wis_rdr = open_data(data_path.clone(), "recipes", "wisdom_commitments.json");
+	let wis_rdr = open_data(data_path, "recipes", "wisdom_commitments.json");
 	let wis_json: WisdomCommitments = serde_json::from_reader(wis_rdr).expect("Failed to parse wisdom commitments file");
 	let commitments: HashMap<_, _> = wis_json.recipes.into_iter().map(|w| (w.id.clone(), w)).collect();
 
-	let skills_rdr = open_data(data_path.clone(), "elements", "skills.json");
+	let skills_rdr = open_data(data_path, "elements", "skills.json");
 	let skills_json: SkillFile = serde_json::from_reader(skills_rdr).expect("Failed to parse skills file");
 	let skills = parse_skills(skills_json, commitments);
 
-	let recipe_rdr = open_data(data_path.clone(), "recipes", "crafting_2_keeper.json");
+	let recipe_rdr = open_data(data_path, "recipes", "crafting_2_keeper.json");
 	let recipe_json: RecipeFile = serde_json::from_reader(recipe_rdr).expect("Failed to parse Keeper recipes");
-	let recipes_keeper: Vec<_> = recipe_json.recipes.into_iter().map(|r| r.into_recipe()).collect();
+	let recipes_keeper: Vec<_> = recipe_json.recipes.into_iter().map(|r| r.into_recipe(&weather)).collect();
 
-	let recipe_rdr = open_data(data_path.clone(), "recipes", "crafting_3_scholar.json");
+	let recipe_rdr = open_data(data_path, "recipes", "crafting_3_scholar.json");
 	let recipe_json: RecipeFile = serde_json::from_reader(recipe_rdr).expect("Failed to parse Scholar recipes");
-	let recipes_scholar: Vec<_> = recipe_json.recipes.into_iter().map(|r| r.into_recipe()).collect();
+	let recipes_scholar: Vec<_> = recipe_json.recipes.into_iter().map(|r| r.into_recipe(&weather)).collect();
 
-	let recipe_rdr = open_data(data_path.clone(), "recipes", "crafting_4b_prentice.json");
+	let recipe_rdr = open_data(data_path, "recipes", "crafting_4b_prentice.json");
 	let recipe_json: RecipeFile = serde_json::from_reader(recipe_rdr).expect("Failed to parse Prentice recipes");
-	let recipes_prentice: Vec<_> = recipe_json.recipes.into_iter().map(|r| r.into_recipe()).collect();
+	let recipes_prentice: Vec<_> = recipe_json.recipes.into_iter().map(|r| r.into_recipe(&weather)).collect();
 
 	Data {
 		items,
 		books,
 		skills,
+		abilities,
+		aspects,
+		languages,
+		visitors,
+		assistance,
+		weather,
+		weather_triggers,
+		families,
+		element_labels,
 		workstations,
+		verbs,
 		recipes: (recipes_prentice, recipes_scholar, recipes_keeper),
 	}
 }
 
-fn open_data(path: PathBuf, dir: &str, file: &str) -> BufReader<File> {
-	let mut p = path.clone();
+fn open_data(path: &Path, dir: &str, file: &str) -> BufReader<File> {
+	let mut p = path.to_path_buf();
 	p.push(dir);
 	p.push(file);
 	let file = match File::open(&p) {
@@ -266,10 +411,137 @@ fn open_data(path: PathBuf, dir: &str, file: &str) -> BufReader<File> {
 	BufReader::new(file)
 }
 
-fn parse_items(item_file: ItemFile, prototypes: HashMap<String, (AspectMap, bool)>) -> HashMap<String, Item> {
+/// Reads every JSON file in a data directory in name order, skipping (with a warning) any that
+/// fail to parse
+fn read_dir<T: DeserializeOwned>(path: &Path, dir: &str) -> Vec<T> {
+	let dir = path.join(dir);
+	let entries = match fs::read_dir(&dir) {
+		Ok(e) => e,
+		Err(_) => panic!("Failed to open game data at {}", dir.to_string_lossy()),
+	};
+	let mut paths: Vec<_> = entries.flatten().map(|e| e.path())
+		.filter(|p| p.extension().is_some_and(|e| e == "json"))
+		.collect();
+	paths.sort();
+	let mut files = Vec::new();
+	for p in paths {
+		let parsed = fs::read(&p).ok()
+			.and_then(|b| decode_text(&b))
+			.and_then(|t| serde_json::from_str(&t).ok());
+		match parsed {
+			Some(file) => files.push(file),
+			None => eprintln!("Warning: Skipping unreadable data file {}", p.to_string_lossy()),
+		}
+	}
+	files
+}
+
+/// Reads every element file
+fn read_elements(path: &Path) -> Vec<SerdeElement> {
+	read_dir::<ElementFile>(path, "elements").into_iter()
+		.flat_map(|f| f.elements)
+		.filter(|e| !e.id.is_empty())
+		.collect()
+}
+
+/// Reads the workstations and other verbs with slots from every verb file. Later files
+/// override earlier ones defining the same verb.
+fn read_verbs(path: &Path) -> (Vec<Workstation>, Vec<Verb>) {
+	let mut workstations = HashMap::new();
+	let mut verbs = HashMap::new();
+	for file in read_dir::<WorkstationFile>(path, "verbs") {
+		for verb in file.verbs.into_iter().filter(|v| !v.id.is_empty()) {
+			match verb.into_verb() {
+				Ok(station) => { workstations.insert(station.id.clone(), station); },
+				Err(verb) if !verb.slots.is_empty() => { verbs.insert(verb.id.clone(), verb); },
+				Err(_) => (),
+			}
+		}
+	}
+	let mut workstations: Vec<_> = workstations.into_values().collect();
+	workstations.sort_by(|a, b| a.label.cmp(&b.label));
+	let mut verbs: Vec<_> = verbs.into_values().collect();
+	verbs.sort_by(|a, b| a.label.cmp(&b.label));
+	(workstations, verbs)
+}
+
+/// Visitors, with what they do for the player from every recipe involving them
+fn read_visitors(path: &Path, elements: &[SerdeElement]) -> HashMap<String, Visitor> {
+	let mut visitors: HashMap<_, _> = elements.iter().filter(|e| e.aspects.contains_key("visitor"))
+		.map(|e| (e.id.clone(), Visitor { label: e.label.clone(), aspects: e.aspects.clone(), offers: Vec::new() }))
+		.collect();
+	for recipe in read_dir::<AnyRecipeFile>(path, "recipes").into_iter().flat_map(|f| f.recipes) {
+		let Some(visitor) = recipe.reqs.keys().find(|r| visitors.contains_key(*r)).cloned() else { continue };
+		let mut gives: Vec<_> = recipe.effects.iter()
+			.filter(|(_, q)| quantity(q).is_some_and(|q| q > 0))
+			.map(|(id, _)| id.clone())
+			.collect();
+		if gives.is_empty() { continue }
+		gives.sort();
+		let mut wants: Vec<_> = recipe.reqs.into_iter()
+			.filter(|(r, _)| *r != visitor)
+			.filter_map(|(r, q)| quantity(&q).filter(|q| *q > 0).map(|q| (r, q as isize)))
+			.collect();
+		wants.sort();
+		visitors.get_mut(&visitor).unwrap().offers.push(VisitorOffer {
+			label: if recipe.label.is_empty() { recipe.id } else { recipe.label },
+			wants,
+			gives,
+		});
+	}
+	visitors
+}
+
+/// Decodes a data file, which may be UTF-8 or UTF-16 with a byte order mark
+fn decode_text(bytes: &[u8]) -> Option<String> {
+	let utf16 = |bytes: &[u8], from: fn([u8; 2]) -> u16| {
+		let units: Vec<_> = bytes.chunks_exact(2).map(|c| from([c[0], c[1]])).collect();
+		String::from_utf16(&units).ok()
+	};
+	match bytes {
+		[0xFF, 0xFE, rest @ ..] => utf16(rest, u16::from_le_bytes),
+		[0xFE, 0xFF, rest @ ..] => utf16(rest, u16::from_be_bytes),
+		[0xEF, 0xBB, 0xBF, rest @ ..] => String::from_utf8(rest.to_vec()).ok(),
+		_ => String::from_utf8(bytes.to_vec()).ok(),
+	}
+}
+
+fn parse_abilities(elements: &[SerdeElement], prototypes: &HashMap<String, (AspectMap, bool)>) -> HashMap<String, Ability> {
+	let mut abilities = HashMap::new();
+	for element in elements {
+		let mut aspects = element.aspects.clone();
+		if let Some(ext) = prototypes.get(&element.inherits) {
+			for (aspect, intensity) in &ext.0 {
+				aspects.entry(aspect.clone()).or_insert(*intensity);
+			}
+		}
+		if aspects.contains_key("ability") {
+			abilities.insert(element.id.clone(), Ability {
+				label: element.label.clone(),
+				aspects,
+			});
+		}
+	}
+	abilities
+}
+
+/// Aspect definitions. Besides those the game marks hidden, aspects without a label are
+/// guessed to be internal and hidden too, as there is nothing to show for them.
+fn parse_aspects(elements: &[SerdeElement]) -> HashMap<String, Aspect> {
+	elements.iter().filter(|e| e.is_aspect).map(|e| (e.id.clone(), Aspect {
+		label: if e.label.is_empty() { e.id.clone() } else { e.label.clone() },
+		description: e.desc.clone(),
+		hidden: e.is_hidden || e.label.is_empty(),
+	})).collect()
+}
+
+fn parse_items(item_file: ItemFile, prototypes: &HashMap<String, (AspectMap, bool)>) -> HashMap<String, Item> {
 	let mut items = HashMap::new();
 
+	let item_ids: HashSet<_> = item_file.elements.iter().map(|i| i.id.clone()).collect();
 	for item in item_file.elements {
+		// Items inheriting from another item start in its family; the rest are grouped later
+		let family = if item_ids.contains(&item.inherits) { item.inherits.clone() } else { String::new() };
 		let mut aspects = item.aspects;
 		let fatigues = if let Some(ext) = prototypes.get(&item.inherits) {
 			for (aspect, intensity) in &ext.0 {
@@ -290,12 +562,61 @@ fn parse_items(item_file: ItemFile, prototypes: HashMap<String, (AspectMap, bool
 			aspects,
 			scrutiny,
 			fatigues,
+			family,
 		});
 	}
 	items
 }
 
-fn parse_books(book_file: BookFile) -> HashMap<String, Book> {
+/// Groups items into families. An item inheriting from another item is its variant.
+/// The game data doesn't link most variants this way, so as a fallback items whose labels
+/// match up to any `(` and which have the same principles are also grouped. This is only a
+/// heuristic: it can miss variants named differently and join unrelated items that share a
+/// name. Families are keyed by their first member's ID.
+fn group_families(items: &mut HashMap<String, Item>) -> HashMap<String, ItemFamily> {
+	let root = |items: &HashMap<String, Item>, id: &String| {
+		let mut id = id.clone();
+		// Bounded in case of an inheritance cycle
+		for _ in 0..items.len() {
+			match items.get(&id).map(|i| &i.family).filter(|f| !f.is_empty()) {
+				Some(parent) => id = parent.clone(),
+				None => break,
+			}
+		}
+		id
+	};
+	let roots: HashMap<_, _> = items.keys().map(|id| (id.clone(), root(items, id))).collect();
+
+	let mut groups: HashMap<(String, Vec<String>), Vec<String>> = HashMap::new();
+	for (id, root) in &roots {
+		let item = &items[root];
+		let mut p: Vec<_> = item.aspects.keys().filter(|a| principles().contains(&a.as_str())).cloned().collect();
+		p.sort();
+		groups.entry((base_label(&item.label).to_string(), p)).or_default().push(id.clone());
+	}
+
+	let mut families = HashMap::new();
+	for ((label, _), mut members) in groups {
+		members.sort();
+		let label = match members.as_slice() {
+			[only] => items[only].label.clone(),
+			_ => label,
+		};
+		for m in &members {
+			items.get_mut(m).unwrap().family = members[0].clone();
+		}
+		families.insert(members[0].clone(), ItemFamily { label, members });
+	}
+	families
+}
+
+/// An item label without any parenthesised variant, e.g. `Candle` for `Candle (Lit)`
+fn base_label(label: &str) -> &str {
+	label.split('(').next().unwrap().trim_end()
+}
+
+/// Books written in a language carry the language's element ID as an aspect
+fn parse_books(book_file: BookFile, languages: &HashMap<String, String>) -> HashMap<String, Book> {
 	let mut books = HashMap::new();
 	for book in book_file.elements {
 		if book.id.is_none() {
@@ -307,12 +628,12 @@ fn parse_books(book_file: BookFile) -> HashMap<String, Book> {
 		for (trigger, res) in book.xtriggers.unwrap().drain() {
 			if trigger.starts_with("mastering") {
 				if res.len() != 1 {
-					println!("Warning: Tome: mastering len was {}. Tome ID: {}", res.len(), book.id.clone().unwrap())
+					eprintln!("Warning: Tome: mastering len was {}. Tome ID: {}", res.len(), book.id.clone().unwrap())
 				}
 				skill = Some((res[0].id.clone(), res[0].level));
 			} else if trigger.starts_with("reading") {
 				if res.len() != 1 {
-					println!("Warning: Tome: reading len was {}. Tome ID: {}", res.len(), book.id.clone().unwrap())
+					eprintln!("Warning: Tome: reading len was {}. Tome ID: {}", res.len(), book.id.clone().unwrap())
 				}
 				memory = Some(res[0].id.clone())
 			}
@@ -325,11 +646,14 @@ fn parse_books(book_file: BookFile) -> HashMap<String, Book> {
 			panic!("No memory returned for book {}", book.label.unwrap())
 		}
 
+		let aspects = book.aspects.unwrap();
+		let language = aspects.keys().find(|a| languages.contains_key(*a)).cloned();
 		books.insert(book.id.unwrap(), Book {
 			label: book.label.unwrap(),
-			aspects: book.aspects.unwrap(),
+			aspects,
 			skill: skill.unwrap(),
 			memory: memory.unwrap(),
+			language,
 		});
 	}
 	books
@@ -338,6 +662,7 @@ fn parse_books(book_file: BookFile) -> HashMap<String, Book> {
 fn parse_skills(skill_file: SkillFile, commitments: HashMap<String, Commitment>) -> HashMap<String, Skill> {
 	let mut skills = HashMap::new();
 	for skill in skill_file.elements {
+		let aspects = skill.aspects.clone();
 		let mut p = skill.aspects.clone().into_iter().filter(|a| principles().contains(&a.0.as_str()));
 		let w = skill.aspects.into_iter().filter(|a| a.0.starts_with("w."));
 		let mut commits = Vec::new();
@@ -359,8 +684,10 @@ fn parse_skills(skill_file: SkillFile, commitments: HashMap<String, Commitment>)
 			commits.push((wisdom.0.clone(), effect));
 		}
 		let mut c = commits.into_iter();
-		skills.insert(skill.id, Skill {
+		skills.insert(skill.id.clone(), Skill {
+			id: skill.id,
 			label: skill.label,
+			aspects,
 			principles: (p.next().unwrap().0, p.next().unwrap().0),
 			wisdoms: (c.next().unwrap(), c.next().unwrap())
 		});
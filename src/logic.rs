@@ -1,36 +1,138 @@
 use std::collections::{HashMap, HashSet};
 
+use serde::{Deserialize, Serialize};
+
 use crate::data::*;
-use crate::save::WorldItem;
+use crate::save::{SaveData, WorldItem};
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
 pub struct Memory {
+	pub id: String,
 	pub label: String,
-	pub source_label: String,
+	pub sources: HashSet<String>,
 	pub aspects: AspectMap,
+	/// Seasons and weather which affect the memory
+	pub weather: Vec<WeatherFlag>,
+}
+
+impl Memory {
+	/// Sum of the memory's intensities in the queried aspects it satisfies
+	pub fn intensity(&self, queries: &[PrincipleQuery]) -> isize {
+		queries.iter().filter(|q| q.matches(&self.aspects))
+			.map(|q| self.aspects[&q.aspect]).sum()
+	}
+}
+
+/// An aspect with an optional minimum intensity, written `lantern`, `lantern>=5` or `moth:2`
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct PrincipleQuery {
+	pub aspect: String,
+	pub min: isize,
+}
+
+impl PrincipleQuery {
+	pub fn parse(query: &str) -> Option<Self> {
+		let query = query.trim();
+		let (aspect, min) = match query.split_once(">=").or_else(|| query.split_once(':')) {
+			Some((a, m)) => (a.trim(), m.trim().parse().ok()?),
+			None => (query, 1),
+		};
+		if aspect.is_empty() { return None }
+		Some(PrincipleQuery { aspect: aspect.to_lowercase(), min })
+	}
+
+	/// Parses a comma separated list of queries, skipping invalid ones
+	pub fn parse_list(queries: &str) -> Vec<Self> {
+		queries.split(',').filter_map(Self::parse).collect()
+	}
+
+	pub fn matches(&self, aspects: &AspectMap) -> bool {
+		aspects.get(&self.aspect).is_some_and(|i| *i >= self.min)
+	}
+}
+
+/// A season or weather which changes an element or is needed by a recipe
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct WeatherFlag {
+	pub label: String,
+	/// Whether it is the current season or weather
+	pub now: bool,
 }
 
+/// Flags for the given seasons, weather or their aspects
+pub fn weather_flags(conditions: &[String], data: &Data, save: &SaveData) -> Vec<WeatherFlag> {
+	let current: Vec<_> = save.season.iter().chain(save.weather.iter()).collect();
+	conditions.iter().map(|c| WeatherFlag {
+		label: data.weather.get(c).map(|w| w.label.clone()).unwrap_or_else(|| data.aspect_label(c).to_string()),
+		now: current.iter().any(|w| *w == c || data.weather.get(*w).is_some_and(|w| w.aspects.contains_key(c))),
+	}).collect()
+}
+
+/// Labels of the current season and weather
+pub fn current_weather(data: &Data, save: &SaveData) -> Vec<String> {
+	save.season.iter().chain(save.weather.iter()).map(|w| data.label(w).to_string()).collect()
+}
+
+/// Flags for the seasons and weather which trigger changes in an element
+pub fn element_weather(id: &str, data: &Data, save: &SaveData) -> Vec<WeatherFlag> {
+	data.weather_triggers.get(id).map(|c| weather_flags(c, data, save)).unwrap_or_default()
+}
+
+/// A boolean query over aspects, e.g. `lantern & tool & !fragile` or `moth>=4 | grail>=4`.
+/// Commas also mean "and", which binds tighter than "or".
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct AspectQuery {
+	/// Alternatives, each a list of terms which must all hold; negated terms must not
+	pub any: Vec<Vec<(bool, PrincipleQuery)>>,
+}
+
+impl AspectQuery {
+	pub fn parse(query: &str) -> Result<Self, String> {
+		let mut any = Vec::new();
+		for alternative in query.split('|') {
+			let mut all = Vec::new();
+			for term in alternative.split(['&', ',']) {
+				let term = term.trim();
+				let (negated, term) = match term.strip_prefix('!') {
+					Some(t) => (true, t),
+					None => (false, term),
+				};
+				let q = PrincipleQuery::parse(term).ok_or_else(|| format!("Invalid aspect query: {}", query))?;
+				all.push((negated, q));
+			}
+			any.push(all);
+		}
+		Ok(AspectQuery { any })
+	}
+
+	pub fn matches(&self, aspects: &AspectMap) -> bool {
+		self.any.iter().any(|all| all.iter().all(|(negated, q)| q.matches(aspects) != *negated))
+	}
+}
+
+/// Finds memories obtainable from the player's items which satisfy any of the queries,
+/// strongest first.
 pub fn find_memories(
-	principles: &[&String],
-	world_items: &Vec<WorldItem>,
+	queries: &[PrincipleQuery],
+	world_items: &[WorldItem],
 	items: &HashMap<String, Item>,
 	books: &HashMap<String, Book>,
-) -> HashMap<String, HashSet<String>> {
+) -> Vec<Memory> {
 	let mut mems = HashMap::new();
 
 	for item in world_items {
 		// Scrutiny
 		if let Some(item) = items.get(&item.id) {
-			if let Some(mem) = item.scrutiny.clone().and_then(|s| items.get(&s)) {
-				if mem.aspects.keys().any(|a| principles.contains(&a)) {
-					ins_ext(&mut mems, &mem.label, &item.label);
+			if let Some((id, mem)) = item.scrutiny.as_ref().and_then(|s| items.get_key_value(s)) {
+				if queries.iter().any(|q| q.matches(&mem.aspects)) {
+					ins_ext(&mut mems, id, mem, &item.label);
 				}
 			}
 			// Beast
 			if let ExhaustType::Beast(b) = &item.fatigues {
 				let mem = items.get(b).expect("Couldn't find item for item ID");
-				if mem.aspects.keys().any(|a| principles.contains(&a)) {
-					ins_ext(&mut mems, &mem.label, &item.label);
+				if queries.iter().any(|q| q.matches(&mem.aspects)) {
+					ins_ext(&mut mems, b, mem, &item.label);
 				}
 			}
 			continue;
@@ -41,113 +143,214 @@ pub fn find_memories(
 			if k.starts_with("mastery") {
 				if let Some(book) = books.get(&item.id) {
 					let mem = items.get(&book.memory).expect("Couldn't find item for item ID");
-					if mem.aspects.keys().any(|a| principles.contains(&a)) {
-						ins_ext(&mut mems, &mem.label, &book.label);
+					if queries.iter().any(|q| q.matches(&mem.aspects)) {
+						ins_ext(&mut mems, &book.memory, mem, &book.label);
 					}
 				}
 				break;
 			}
 		}
 	}
+	let mut mems: Vec<_> = mems.into_values().collect();
+	mems.sort_by(|a, b| b.intensity(queries).cmp(&a.intensity(queries)).then_with(|| a.label.cmp(&b.label)));
+	mems
+}
+
+/// Flags the seasons and weather affecting each memory
+pub fn with_weather(mut mems: Vec<Memory>, data: &Data, save: &SaveData) -> Vec<Memory> {
+	for mem in &mut mems {
+		mem.weather = element_weather(&mem.id, data, save);
+	}
 	mems
 }
 
-fn ins_ext(map: &mut HashMap<String, HashSet<String>>, mem: &String, item: &String) {
-	if let Err(mut e) = map.try_insert(mem.clone(), HashSet::from([item.clone()])) {
-		e.entry.get_mut().insert(item.clone());
+/// Finds memories from scrutinising items which the player's skills can craft
+pub fn craftable_memories(queries: &[PrincipleQuery], data: &Data, save: &SaveData) -> Vec<Memory> {
+	let mut mems = HashMap::new();
+	for recipe in data.all_recipes().filter(|r| save.skills.iter().any(|s| s.id == r.skill)) {
+		for (id, _) in &recipe.effects {
+			let mem = data.items.get(id).and_then(|i| i.scrutiny.as_ref()).and_then(|s| data.items.get_key_value(s));
+			if let Some((id, mem)) = mem.filter(|(_, m)| queries.iter().any(|q| q.matches(&m.aspects))) {
+				ins_ext(&mut mems, id, mem, &recipe.label);
+			}
+		}
 	}
+	let mut mems: Vec<_> = mems.into_values().collect();
+	mems.sort_by(|a, b| b.intensity(queries).cmp(&a.intensity(queries)).then_with(|| a.label.cmp(&b.label)));
+	with_weather(mems, data, save)
 }
 
-pub fn get_skill_stations(skills: &Vec<&Skill>, workstations: &[Workstation]) -> String {
-	let mut res = String::new();
-	for skill in skills {
-		res.push_str(&format!("<h3>{}</h3>", skill.label));
-		res.push_str(&add_commitment(&skill.wisdoms.0, skill, workstations));
-		res.push_str(&add_commitment(&skill.wisdoms.1, skill, workstations));
+fn ins_ext(map: &mut HashMap<String, Memory>, id: &str, mem: &Item, source: &str) {
+	match map.try_insert(mem.label.clone(), Memory {
+		id: id.to_owned(),
+		label: mem.label.clone(),
+		sources: HashSet::from([source.to_owned()]),
+		aspects: mem.aspects.clone(),
+		weather: Vec::new(),
+	}) {
+		Ok(_) => (),
+		Err(mut e) => { e.entry.get_mut().sources.insert(source.to_owned()); },
 	}
-	res
 }
 
-fn add_commitment(commit: &(String, String), skill: &Skill, workstations: &[Workstation]) -> String {
-	let wisdom = commit.0.split('.').nth(1)	.unwrap();
-	let soul = principles_from_soul(&commit.1);
-	let id = "e.".to_string() + wisdom;
-	let stations: Vec<_> = workstations.iter().filter(|w|
-		w.wisdoms.contains(&id) &&
-		w.accepts_principles(&[&skill.principles.0, &skill.principles.1]) &&
-		w.accepts_principles(soul.1.as_slice()))
-		.map(|w| w.label.clone()).collect();
-	match stations.len() {
-		0 => format!("<p>Warning: {} can't be upgraded when committed to {}</p>", soul.0, wisdom),
-		_ => format!(         "<p>{} is upgraded at {} when committed to {}</p>", soul.0, dis_vec(&stations), wisdom),
+/// Skills whose label starts with the input, or only the exact match if there is one
+pub fn matching_skills<'a>(data: &'a Data, input: &str) -> Vec<(&'a String, &'a Skill)> {
+	let input = input.trim().to_lowercase();
+	if let Some(exact) = data.skills.iter().find(|(_, s)| s.label.to_lowercase() == input) {
+		return vec![exact];
 	}
+	let mut found: Vec<_> = data.skills.iter()
+		.filter(|(_, s)| s.label.to_lowercase().starts_with(&input))
+		.collect();
+	found.sort_by(|a, b| a.1.label.cmp(&b.1.label));
+	found
 }
 
-pub fn find_aspected(items: &HashMap<String, Item>, aspects: &[&str]) -> HashMap<String, AspectMap> {
-	let mut found = HashMap::new();
+/// What a form field holds, for suggestions
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SuggestKind {
+	/// Principle queries, separated by commas
+	Principle,
+	/// Aspect queries, combined with `,`, `&`, `|` and `!`
+	Aspect,
+	Skill,
+	Item,
+	/// A solver target: principle, recipe or book
+	Target,
+	/// A planner goal: item, memory or aspect
+	Goal,
+}
 
-	for item in items.values() {
-		let label = if item.label.starts_with("Lepidoptery") || item.label.starts_with("Wire") {
-			&item.label
-		} else {
-			item.label.split('(').next().unwrap()
-		};
-		if found.contains_key(label) { continue; }
+/// Completions for what has been typed so far. Lists are completed on their last entry.
+pub fn suggest(data: &Data, kind: SuggestKind, input: &str, limit: usize) -> Vec<String> {
+	let aspects = || {
+		let mut aspects: Vec<_> = data.items.values().flat_map(|i| i.aspects.keys()).map(|a| a.as_str()).collect();
+		aspects.sort();
+		aspects.dedup();
+		aspects
+	};
+	let (prefix, last) = match kind {
+		SuggestKind::Principle => input.split_at(input.rfind(',').map(|i| i + 1).unwrap_or(0)),
+		SuggestKind::Aspect => input.split_at(input.rfind([',', '&', '|', '!']).map(|i| i + 1).unwrap_or(0)),
+		_ => ("", input),
+	};
+	// Keep the spacing typed after a separator
+	let prefix = &input[..prefix.len() + last.len() - last.trim_start().len()];
+	let candidates: Vec<&str> = match kind {
+		SuggestKind::Principle => principles(),
+		SuggestKind::Aspect => aspects(),
+		SuggestKind::Skill => data.skills.values().map(|s| s.label.as_str()).collect(),
+		SuggestKind::Item => data.items.values().map(|i| i.label.as_str()).collect(),
+		SuggestKind::Target => principles().into_iter()
+			.chain(data.all_recipes().map(|r| r.label.as_str()))
+			.chain(data.books.values().map(|b| b.label.as_str()))
+			.collect(),
+		SuggestKind::Goal => data.items.values().map(|i| i.label.as_str()).chain(aspects()).collect(),
+	};
 
-		if aspects.iter().all(|a| item.aspects.contains_key(a.to_owned())) {
-			found.insert(label.to_owned(), item.aspects.clone());
-		}
-	}
+	let last = last.trim().to_lowercase();
+	if last.is_empty() || last.contains([':', '>', '=']) { return Vec::new() }
+	let mut starts: Vec<_> = candidates.iter().filter(|c| c.to_lowercase().starts_with(&last)).collect();
+	let mut contains: Vec<_> = candidates.iter().filter(|c| !c.to_lowercase().starts_with(&last) && c.to_lowercase().contains(&last)).collect();
+	starts.sort();
+	contains.sort();
+	let mut found: Vec<_> = starts.into_iter().chain(contains).map(|c| format!("{}{}", prefix, c)).collect();
+	found.dedup();
+	found.truncate(limit);
 	found
 }
 
-pub fn dis_vec(v: &Vec<String>) -> String {
-	match v.len() {
-		0 => String::new(),
-		1 => v[0].to_string(),
-		2 => format!("{} or {}", v[0].clone(), v[1].clone()),
-		l => {
-			let mut res = String::new();
-			for i in 0..(l-1) {
-				res.push_str(&fmt_name(v[i].to_string()));
-				res.push_str(", ");
-			}
-			res.push_str("or ");
-			res.push_str(&fmt_name(v[l-1].to_string()));
-			res
-		}
-	}
+/// Workstations in unlocked rooms which can craft the recipe
+pub fn craft_stations<'a>(recipe: &Recipe, data: &'a Data, save: &SaveData) -> Vec<&'a Workstation> {
+	data.workstations.iter()
+		.filter(|w| save.has_workstation(w) && w.can_craft(recipe, data))
+		.collect()
 }
 
-pub fn dis_set(v: &HashSet<String>) -> String {
-	match v.len() {
-		0 => String::new(),
-		1 => v.iter().next().unwrap().to_string(),
-		2 => {
-			let mut it = v.iter();
-			format!("{} or {}", it.next().unwrap().to_string(), it.next().unwrap().to_string())
-		},
-		_ => {
-			let mut qty = 0;
-			let mut res = String::new();
-			let mut it = v.iter().peekable();
-			loop {
-				let item = it.next().unwrap();
-				if it.peek().is_none() || qty == 8 {
-					res.push_str("or ");
-					res.push_str(&fmt_name(item.to_string()));
-					return res;
-				}
-				res.push_str(&fmt_name(item.to_string()));
-				res.push_str(", ");
-				qty += 1;
-			}
+/// Items matching the query as (ID, aspects), in groups of one family's variants
+/// unless `variants` is set, in which case each item is its own group
+pub fn find_aspected<'a>(
+	items: impl Iterator<Item = (&'a String, AspectMap)>,
+	query: &AspectQuery,
+	variants: bool,
+	data: &Data,
+) -> Vec<Vec<(String, AspectMap)>> {
+	let mut found: HashMap<&String, Vec<(String, AspectMap)>> = HashMap::new();
+
+	for (id, aspects) in items {
+		if !query.matches(&aspects) { continue; }
+		let key = if variants { id } else { &data.items[id].family };
+		let group = found.entry(key).or_default();
+		if group.iter().all(|(i, _)| i != id) {
+			group.push((id.clone(), aspects));
 		}
 	}
+	found.into_values().map(|mut g| {
+		g.sort_by(|a, b| a.0.cmp(&b.0));
+		g
+	}).collect()
 }
 
-fn fmt_name(name: String) -> String {
-	if name.contains(',') {
-		format!("'{}'", name)
-	} else { name }
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn q(aspect: &str, min: isize) -> PrincipleQuery {
+		PrincipleQuery { aspect: aspect.to_string(), min }
+	}
+
+	#[test]
+	fn principle_query_forms() {
+		assert_eq!(PrincipleQuery::parse("lantern"), Some(q("lantern", 1)));
+		assert_eq!(PrincipleQuery::parse("Lantern>=5"), Some(q("lantern", 5)));
+		assert_eq!(PrincipleQuery::parse(" lantern >= 5 "), Some(q("lantern", 5)));
+		assert_eq!(PrincipleQuery::parse("moth:2"), Some(q("moth", 2)));
+	}
+
+	#[test]
+	fn principle_query_invalid() {
+		assert_eq!(PrincipleQuery::parse(""), None);
+		assert_eq!(PrincipleQuery::parse(">=5"), None);
+		assert_eq!(PrincipleQuery::parse("lantern>="), None);
+		assert_eq!(PrincipleQuery::parse("moth:two"), None);
+	}
+
+	#[test]
+	fn principle_list_splits_on_commas() {
+		assert_eq!(PrincipleQuery::parse_list("lantern >= 5, moth:2"), vec![q("lantern", 5), q("moth", 2)]);
+		assert_eq!(PrincipleQuery::parse_list("lantern,, moth:x,"), vec![q("lantern", 1)]);
+		assert_eq!(PrincipleQuery::parse_list("lantern moth"), vec![q("lantern moth", 1)]);
+	}
+
+	fn aspects(list: &[(&str, isize)]) -> AspectMap {
+		list.iter().map(|(a, i)| (a.to_string(), *i)).collect()
+	}
+
+	#[test]
+	fn aspect_query_terms() {
+		let query = AspectQuery::parse("lantern>=2 & tool, !fragile").unwrap();
+		assert_eq!(query.any, vec![vec![(false, q("lantern", 2)), (false, q("tool", 1)), (true, q("fragile", 1))]]);
+		assert!(query.matches(&aspects(&[("lantern", 2), ("tool", 1)])));
+		assert!(!query.matches(&aspects(&[("lantern", 1), ("tool", 1)])));
+		assert!(!query.matches(&aspects(&[("lantern", 2), ("tool", 1), ("fragile", 1)])));
+	}
+
+	#[test]
+	fn aspect_query_and_binds_tighter_than_or() {
+		let query = AspectQuery::parse("moth & tool | grail").unwrap();
+		assert_eq!(query.any.len(), 2);
+		assert!(query.matches(&aspects(&[("grail", 1)])));
+		assert!(query.matches(&aspects(&[("moth", 1), ("tool", 1)])));
+		assert!(!query.matches(&aspects(&[("moth", 1)])));
+	}
+
+	#[test]
+	fn aspect_query_invalid() {
+		assert!(AspectQuery::parse("").is_err());
+		assert!(AspectQuery::parse("lantern &").is_err());
+		assert!(AspectQuery::parse("lantern | ").is_err());
+		assert!(AspectQuery::parse("!").is_err());
+		assert!(AspectQuery::parse("moth:x").is_err());
+	}
 }
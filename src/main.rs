@@ -1,38 +1,69 @@
-#![feature(str_from_utf16_endian, map_try_insert, string_remove_matches)]
+#![feature(map_try_insert)]
 
 use std::{
-	env::args, fs, path::PathBuf, sync::{Arc, Mutex}, time::Duration
+	env::{args, var}, fs, path::{Path, PathBuf}, process::exit, sync::{Arc, Mutex}, time::Duration
 };
 
-use axum::{routing::get, Router};
-use tokio::time::sleep;
-use tower::ServiceBuilder;
-use tower_http::services::ServeDir;
+use axum::{middleware, routing::{get, post}, Router};
+use tokio::{signal, time::sleep};
 
+mod api;
 mod app;
+mod assets;
+mod auth;
+mod cli;
 mod data;
 mod logic;
+mod planner;
+mod report;
 mod save;
+mod search;
+mod solver;
+mod tui;
 mod ui;
 
 use app::*;
+use auth::Access;
 use data::init_items;
 use save::{default_save_path, SaveData};
 
 #[tokio::main]
 async fn main() {
-	let mut data_path = PathBuf::from(args().nth(1).expect("Data path required"));
+	let mut args: Vec<_> = args().skip(1).collect();
+	let host = take_flag(&mut args, "--host", "BOH_HOST").unwrap_or(String::from("127.0.0.1"));
+	let port = take_flag(&mut args, "--port", "BOH_PORT").map(|p| p.parse::<u16>().expect("Invalid port")).unwrap_or(3000);
+	let access = match (take_flag(&mut args, "--token", "BOH_TOKEN"), take_flag(&mut args, "--auth", "BOH_AUTH")) {
+		(Some(_), Some(_)) => panic!("Use either a token or basic auth, not both"),
+		(Some(token), None) => Access::Token(token),
+		(None, Some(credentials)) if credentials.contains(':') => Access::Basic(credentials),
+		(None, Some(_)) => panic!("Basic auth must be given as user:password"),
+		(None, None) => Access::Open,
+	};
+	let asset_overrides = take_flag(&mut args, "--assets", "BOH_ASSETS").map(PathBuf::from);
+	let game_path = match args.first() {
+		Some(a) if !cli::COMMANDS.contains(&a.as_str()) && Path::new(a).is_dir() => Some(args.remove(0)),
+		_ => var("BOH_GAME_PATH").ok(),
+	};
+	if args.first().is_some_and(|a| !cli::COMMANDS.contains(&a.as_str())) {
+		eprintln!("{}", cli::USAGE);
+		exit(1);
+	}
+	let mut data_path = PathBuf::from(game_path.expect("Data path required"));
 	data_path.extend(["StreamingAssets", "bhcontent", "core"]);
-	println!("Using game path: {}", data_path.to_string_lossy());
+	eprintln!("Using game path: {}", data_path.to_string_lossy());
 	let data = init_items(&data_path);
 
 	let path = default_save_path();
-	println!("Using save path {}", path.to_string_lossy());
-	let mut save = SaveData::from_path(path.clone());
-	save.items = save.items.into_iter().filter(|i|
-		data.items.contains_key(&i.id) ||
-		data.books.contains_key(&i.id) ||
-		data.skills.contains_key(&i.id)).collect();
+	eprintln!("Using save path {}", path.to_string_lossy());
+	let save = SaveData::load(path.clone(), &data);
+
+	if args.first().is_some_and(|a| a == "tui") {
+		tui::run(&data, save, path).expect("Terminal UI failed");
+		return;
+	}
+	if !args.is_empty() {
+		exit(if cli::run(&args, &data, &save) { 0 } else { 1 });
+	}
 
 	let state = AppState {
 		data: Arc::new(data),
@@ -47,15 +78,16 @@ async fn main() {
 			sleep(Duration::from_secs(10)).await;
 			if let Ok(time) = fs::metadata(&path).and_then(|m| m.modified()) {
 				if modified != time {
-					modified = time;
-					let mut new_save = SaveData::from_path(path.clone());
-					new_save.items = new_save.items.into_iter().filter(|i|
-						data.items.contains_key(&i.id) ||
-						data.books.contains_key(&i.id) ||
-						data.skills.contains_key(&i.id)).collect();
-					let mut s = save.lock().unwrap();
-					*s = new_save;
-					drop(s);
+					// A half-written save fails to load; keep the old one and retry next time
+					match SaveData::try_load(path.clone(), &data) {
+						Ok(new_save) => {
+							modified = time;
+							let mut s = save.lock().unwrap();
+							*s = new_save;
+							drop(s);
+						},
+						Err(e) => eprintln!("Keeping the previous save: {}", e),
+					}
 				}
 			}
 		}
@@ -65,14 +97,67 @@ async fn main() {
 		.route("/", get(root))
 		.route("/find_mems", get(p_form).post(find_mems))
 		.route("/solve", get(s_form).post(solve))
+		.route("/solve/slots", post(solve_slots))
 		.route("/crafting", get(c_form).post(crafting))
+		.route("/plan", get(pl_form).post(planner))
 		.route("/items", get(i_form).post(items))
-		.nest_service("/assets",
-			ServiceBuilder::new()
-			.service(ServeDir::new("assets")))
+		.route("/search", get(search_page))
+		.route("/workstations", get(workstations))
+		.route("/library", get(library))
+		.route("/visitors", get(visitors))
+		.route("/assistance", get(assistance))
+		.route("/item/:id", get(item_page))
+		.route("/book/:id", get(book_page))
+		.route("/skill/:id", get(skill_page))
+		.route("/workstation/:id", get(workstation_page))
+		.nest("/api/v1", Router::new()
+			.route("/memories", get(api::memories))
+			.route("/solve", get(api::solve))
+			.route("/solve/slots", get(api::solve_slots))
+			.route("/crafting", get(api::crafting))
+			.route("/plan", get(api::plan))
+			.route("/items", get(api::items))
+			.route("/skills", get(api::skills))
+			.route("/aspects", get(api::aspects))
+			.route("/workstations", get(api::workstations))
+			.route("/item/:id", get(api::item))
+			.route("/book/:id", get(api::book))
+			.route("/skill/:id", get(api::skill))
+			.route("/workstation/:id", get(api::workstation))
+			.route("/recipes", get(api::recipes))
+			.route("/library", get(api::library))
+			.route("/visitors", get(api::visitors))
+			.route("/assistance", get(api::assistance))
+			.route("/suggest", get(api::suggestions))
+			.route("/search", get(api::search)))
+		.nest("/assets", Router::new()
+			.route("/*file", get(assets::serve))
+			.with_state(asset_overrides))
+		.layer(middleware::from_fn_with_state(access.clone(), auth::check))
 		.with_state(state);
-	let listener = tokio::net::TcpListener::bind("0.0.0.0:3000")
+
+	let listener = tokio::net::TcpListener::bind((host.as_str(), port))
 		.await
 		.unwrap();
-	axum::serve(listener, app).await.unwrap();
+	let addr = listener.local_addr().unwrap();
+	if !addr.ip().is_loopback() && access.is_open() {
+		eprintln!("Warning: Serving {} to the network without --token or --auth", addr);
+	}
+	eprintln!("Listening on http://{}", addr);
+	axum::serve(listener, app)
+		.with_graceful_shutdown(async { signal::ctrl_c().await.expect("Failed to listen for Ctrl-C") })
+		.await
+		.unwrap();
+}
+
+/// Removes `--flag value` from the arguments, falling back to an environment variable
+fn take_flag(args: &mut Vec<String>, flag: &str, env: &str) -> Option<String> {
+	match args.iter().position(|a| a == flag) {
+		Some(i) if i + 1 < args.len() => {
+			args.remove(i);
+			Some(args.remove(i))
+		},
+		Some(_) => panic!("Missing value for {}", flag),
+		None => var(env).ok(),
+	}
 }
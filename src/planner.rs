@@ -0,0 +1,179 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::data::*;
+use crate::logic::craft_stations;
+use crate::report::EntityLink;
+use crate::save::SaveData;
+
+/// How many crafts deep to search for ingredients
+const MAX_DEPTH: usize = 4;
+
+/// Something the player wants to end up holding
+#[derive(Serialize, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde(tag = "type", content = "id", rename_all = "lowercase")]
+pub enum Goal {
+	/// A specific item or memory, by ID
+	Item(String),
+	/// Any item with the aspect
+	Aspect(String),
+}
+
+impl Goal {
+	/// Interprets an exact item or memory label, then an aspect, then the start of a label
+	pub fn parse(input: &str, data: &Data) -> Option<Self> {
+		let lower = input.trim().to_lowercase();
+		if lower.is_empty() { return None }
+		let mut items: Vec<_> = data.items.iter().filter(|(_, i)| i.label.to_lowercase().starts_with(&lower)).collect();
+		// Exact labels first, then the shortest label the input starts
+		items.sort_by_key(|(id, i)| (i.label.to_lowercase() != lower, i.label.len(), *id));
+		match items.first() {
+			Some((id, i)) if i.label.to_lowercase() == lower => return Some(Goal::Item(id.to_string())),
+			_ => (),
+		}
+		if data.items.values().any(|i| i.aspects.contains_key(&lower)) {
+			return Some(Goal::Aspect(lower));
+		}
+		items.first().map(|(id, _)| Goal::Item(id.to_string()))
+	}
+
+	fn satisfied_by(&self, id: &str, data: &Data) -> bool {
+		match self {
+			Goal::Item(goal) => goal == id,
+			Goal::Aspect(aspect) => data.items.get(id).is_some_and(|i| i.aspects.contains_key(aspect)),
+		}
+	}
+}
+
+/// One step of a plan, in the order they should be carried out
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "step", rename_all = "lowercase")]
+pub enum Step {
+	/// Use an item the player already has
+	Have { item: EntityLink },
+	/// Scrutinise, read or exhaust an item to gain a memory
+	Recall { item: EntityLink, memory: EntityLink },
+	Craft {
+		recipe: String,
+		skill: EntityLink,
+		workstations: Vec<EntityLink>,
+		/// Ingredient item or aspect, by label
+		ingredient: Option<String>,
+		output: EntityLink,
+	},
+}
+
+struct Planner<'a> {
+	data: &'a Data,
+	save: &'a SaveData,
+	owned: HashSet<&'a str>,
+	memo: HashMap<Goal, Option<Vec<Step>>>,
+	visiting: HashSet<Goal>,
+	/// Whether the current search hit the depth limit or a cycle
+	cut: bool,
+}
+
+/// Finds the shortest chain of crafts and recollections which ends with the goal,
+/// preferring items the player already owns
+pub fn plan(goal: &Goal, data: &Data, save: &SaveData) -> Option<Vec<Step>> {
+	let mut planner = Planner {
+		data,
+		save,
+		owned: save.items.iter().map(|i| i.id.as_str()).collect(),
+		memo: HashMap::new(),
+		visiting: HashSet::new(),
+		cut: false,
+	};
+	planner.solve(goal, 0)
+}
+
+impl Planner<'_> {
+	fn solve(&mut self, goal: &Goal, depth: usize) -> Option<Vec<Step>> {
+		if let Some(res) = self.memo.get(goal) {
+			return res.clone();
+		}
+		if depth > MAX_DEPTH || !self.visiting.insert(goal.clone()) {
+			self.cut = true;
+			return None;
+		}
+		let outer = std::mem::take(&mut self.cut);
+		let res = self.search(goal, depth);
+		self.visiting.remove(goal);
+		// A search that was cut short may find more when the goal is reached another way
+		if !self.cut {
+			self.memo.insert(goal.clone(), res.clone());
+		}
+		self.cut |= outer;
+		res
+	}
+
+	fn search(&mut self, goal: &Goal, depth: usize) -> Option<Vec<Step>> {
+		let data = self.data;
+		let mut owned: Vec<_> = self.owned.iter().filter(|id| goal.satisfied_by(id, data)).collect();
+		owned.sort();
+		if let Some(id) = owned.first() {
+			return Some(vec![Step::Have { item: EntityLink::item(id, data) }]);
+		}
+
+		let mut best: Option<Vec<Step>> = None;
+		let mut consider = |plan: Vec<Step>| {
+			if best.as_ref().is_none_or(|b| plan.len() < b.len()) {
+				best = Some(plan);
+			}
+		};
+
+		// Memories come from scrutinising items, exhausting beasts, or reading books
+		if let Goal::Item(mem) = goal {
+			let mut sources: Vec<_> = data.items.iter().filter(|(_, i)|
+				i.scrutiny.as_ref() == Some(mem) ||
+				matches!(&i.fatigues, ExhaustType::Beast(b) if b == mem))
+				.map(|(id, _)| id.clone()).collect();
+			sources.sort();
+			for source in sources {
+				if let Some(mut plan) = self.solve(&Goal::Item(source.clone()), depth + 1) {
+					plan.push(Step::Recall { item: EntityLink::item(&source, data), memory: EntityLink::item(mem, data) });
+					consider(plan);
+				}
+			}
+			let mut books: Vec<_> = data.books.iter().filter(|(id, b)| &b.memory == mem && self.owned.contains(id.as_str())).collect();
+			books.sort_by_key(|(id, _)| *id);
+			if let Some((id, _)) = books.first() {
+				consider(vec![
+					Step::Have { item: EntityLink::book(id, data) },
+					Step::Recall { item: EntityLink::book(id, data), memory: EntityLink::item(mem, data) },
+				]);
+			}
+		}
+
+		let mut recipes: Vec<_> = data.all_recipes()
+			.filter(|r| self.save.skills.iter().any(|s| s.id == r.skill))
+			.filter_map(|r| r.effects.iter().find(|(id, _)| goal.satisfied_by(id, data)).map(|(id, _)| (r, id)))
+			.collect();
+		recipes.sort_by(|a, b| a.0.label.cmp(&b.0.label));
+		for (recipe, output) in recipes {
+			let workstations: Vec<_> = craft_stations(recipe, data, self.save).into_iter().map(EntityLink::workstation).collect();
+			if workstations.is_empty() { continue }
+			let mut plan = match &recipe.ingredient {
+				Some(i) if data.items.contains_key(i) => match self.solve(&Goal::Item(i.clone()), depth + 1) {
+					Some(p) => p,
+					None => continue,
+				},
+				Some(i) => match self.solve(&Goal::Aspect(i.clone()), depth + 1) {
+					Some(p) => p,
+					None => continue,
+				},
+				None => Vec::new(),
+			};
+			plan.push(Step::Craft {
+				recipe: recipe.label.clone(),
+				skill: EntityLink::skill(&recipe.skill, data),
+				workstations,
+				ingredient: recipe.ingredient.as_ref().map(|i| data.label(i).to_string()),
+				output: EntityLink::item(output, data),
+			});
+			consider(plan);
+		}
+		best
+	}
+}
@@ -0,0 +1,743 @@
+use std::{collections::HashSet, fmt};
+
+use serde::Serialize;
+
+use crate::data::*;
+use crate::logic::{craft_stations, craftable_memories, current_weather, element_weather, find_aspected, find_memories, matching_skills, weather_flags, with_weather, AspectQuery, Memory, PrincipleQuery, WeatherFlag};
+use crate::planner::{plan, Goal, Step};
+use crate::save::SaveData;
+use crate::search::EntityKind;
+use crate::solver::{solve_all, solve_station, Hand, Solution, Target};
+
+/// Why a query gave no report
+#[derive(Clone, Debug)]
+pub enum QueryError {
+	/// The query couldn't be understood or is ambiguous
+	Invalid(String),
+	/// Nothing matches the name given
+	NotFound(String),
+}
+
+impl fmt::Display for QueryError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			QueryError::Invalid(e) | QueryError::NotFound(e) => f.write_str(e),
+		}
+	}
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct MemoryReport {
+	pub queries: Vec<PrincipleQuery>,
+	/// Memories from the player's items
+	pub memories: Vec<Memory>,
+	/// Memories from items the player can craft
+	pub craftable: Vec<Memory>,
+	/// Current season and weather
+	pub now: Vec<String>,
+}
+
+impl MemoryReport {
+	pub fn new(principles: &str, data: &Data, save: &SaveData) -> Self {
+		let queries = PrincipleQuery::parse_list(principles);
+		MemoryReport {
+			memories: with_weather(find_memories(&queries, &save.items, &data.items, &data.books), data, save),
+			craftable: craftable_memories(&queries, data, save),
+			now: current_weather(data, save),
+			queries,
+		}
+	}
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SolveReport {
+	pub skills: Vec<SkillStationReport>,
+	pub memories: Vec<Memory>,
+}
+
+impl SolveReport {
+	pub fn new(p1: &str, p2: &str, data: &Data, save: &SaveData) -> Self {
+		let queries: Vec<_> = [p1, p2].into_iter().filter_map(PrincipleQuery::parse).collect();
+		let principles: Vec<_> = queries.iter().map(|q| q.aspect.clone()).collect();
+		let mut skills: Vec<_> = data.skills.iter()
+			.filter(|(_, s)| s.matches(&principles))
+			.collect();
+		skills.sort_by(|a, b| a.1.label.cmp(&b.1.label));
+		SolveReport {
+			skills: get_skill_stations(&skills, &data.workstations),
+			memories: with_weather(find_memories(&queries, &save.items, &data.items, &data.books), data, save),
+		}
+	}
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SlotReport {
+	pub target: Target,
+	/// Solutions grouped by workstation, best workstation first
+	pub workstations: Vec<Vec<Solution>>,
+	/// Current season and weather
+	pub now: Vec<String>,
+}
+
+impl SlotReport {
+	/// Solves at the workstation with the verb ID, or at every workstation if none is given
+	pub fn new(workstation: &str, target: &str, data: &Data, save: &SaveData) -> Result<Self, QueryError> {
+		let target = Target::parse(target, data).ok_or(QueryError::NotFound(format!("Unknown target: {}", target)))?;
+		let hand = Hand::new(data, save);
+		let workstations = if workstation.is_empty() {
+			solve_all(&target, &hand, data)
+		} else {
+			let station = data.workstations.iter().find(|w| w.id == workstation)
+				.ok_or(QueryError::NotFound(format!("Workstation not found: {}", workstation)))?;
+			vec![solve_station(station, &target, &hand, data)]
+		};
+		Ok(SlotReport { target, workstations, now: current_weather(data, save) })
+	}
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct CraftingReport {
+	pub id: String,
+	pub skill: String,
+	pub recipes: Vec<RecipeReport>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct RecipeReport {
+	pub label: String,
+	pub level: RecipeLevel,
+	pub principle: String,
+	pub amount: isize,
+	pub ingredient: Option<String>,
+	/// Whether the player already knows a recipe of this name
+	pub known: bool,
+	pub makes: Vec<ProductReport>,
+	pub workstations: Vec<StationReport>,
+	/// Seasons and weather the recipe needs
+	pub weather: Vec<WeatherFlag>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ProductReport {
+	pub id: String,
+	pub label: String,
+	pub quantity: isize,
+	pub aspects: AspectMap,
+	/// Memory gained by scrutinising the product
+	pub memory: Option<ItemReport>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct StationReport {
+	pub id: String,
+	pub label: String,
+	pub room: Option<String>,
+}
+
+impl CraftingReport {
+	/// Recipes of the skill named, or uniquely prefixed, by `skill`
+	pub fn new(skill: &str, data: &Data, save: &SaveData) -> Result<Self, QueryError> {
+		match matching_skills(data, skill).as_slice() {
+			[] => Err(QueryError::NotFound(format!("Skill not found: {}", skill))),
+			[(id, _)] => Ok(Self::for_skill(id, data, save)),
+			many => {
+				let labels: Vec<_> = many.iter().map(|(_, s)| s.label.as_str()).collect();
+				Err(QueryError::Invalid(format!("Ambiguous skill {}: could be {}", skill, labels.join(", "))))
+			},
+		}
+	}
+
+	/// Recipes of the skill with the given ID, which must exist
+	pub fn for_skill(skill_id: &str, data: &Data, save: &SaveData) -> Self {
+		let known_recipes: HashSet<_> = data.all_recipes()
+			.filter(|r| save.skills.iter().any(|s| s.id == r.skill))
+			.map(|r| r.label.clone()).collect();
+
+		let mut recipes = Vec::new();
+		let levels = [
+			(&data.recipes.0, RecipeLevel::Prentice),
+			(&data.recipes.1, RecipeLevel::Scholar),
+			(&data.recipes.2, RecipeLevel::Keeper),
+		];
+		for (level_recipes, level) in levels {
+			for r in level_recipes.iter().filter(|r| skill_id == r.skill) {
+				let ingredient = match level {
+					RecipeLevel::Prentice => None,
+					RecipeLevel::Scholar => r.ingredient.clone(),
+					RecipeLevel::Keeper => r.ingredient.as_ref().map(|i| data.label(i).to_string()),
+				};
+				let makes = r.effects.iter().map(|(id, qty)| {
+					let made = data.items.get(id);
+					ProductReport {
+						id: id.clone(),
+						label: data.label(id).to_string(),
+						quantity: *qty,
+						aspects: made.map(|m| m.aspects.clone()).unwrap_or_default(),
+						memory: made.and_then(|m| m.scrutiny.as_ref()).filter(|s| data.items.contains_key(*s))
+							.map(|s| ItemReport::new(s, data, save)),
+					}
+				}).collect();
+				let workstations = craft_stations(r, data, save).into_iter().map(|w| StationReport {
+					id: w.id.clone(),
+					label: w.label.clone(),
+					room: save.workstation_room(w, data).map(String::from),
+				}).collect();
+				recipes.push(RecipeReport {
+					label: r.label.clone(),
+					level,
+					principle: r.principle.clone(),
+					amount: r.amount,
+					ingredient,
+					known: known_recipes.contains(&r.label),
+					makes,
+					workstations,
+					weather: weather_flags(&r.weather, data, save),
+				});
+			}
+		}
+		CraftingReport { id: skill_id.to_string(), skill: data.label(skill_id).to_string(), recipes }
+	}
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ItemReport {
+	pub id: String,
+	pub label: String,
+	/// Aspects shared by every listed variant
+	pub aspects: AspectMap,
+	/// Variants of a family matching a query, with the aspects they differ in
+	pub variants: Vec<VariantReport>,
+	/// Seasons and weather which affect the item
+	pub weather: Vec<WeatherFlag>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct VariantReport {
+	pub id: String,
+	pub label: String,
+	pub aspects: AspectMap,
+	pub weather: Vec<WeatherFlag>,
+}
+
+impl ItemReport {
+	fn new(id: &str, data: &Data, save: &SaveData) -> Self {
+		ItemReport {
+			id: id.to_string(),
+			label: data.label(id).to_string(),
+			aspects: data.items[id].aspects.clone(),
+			variants: Vec::new(),
+			weather: element_weather(id, data, save),
+		}
+	}
+
+	/// A family's matching variants as one entry, or the item itself if it is the only one
+	fn group(mut found: Vec<(String, AspectMap)>, data: &Data, save: &SaveData) -> Self {
+		if found.len() == 1 {
+			let (id, aspects) = found.remove(0);
+			return ItemReport { aspects, ..ItemReport::new(&id, data, save) };
+		}
+		let mut common = found[0].1.clone();
+		common.retain(|a, i| found.iter().all(|(_, v)| v.get(a) == Some(i)));
+		let variants = found.iter().map(|(id, aspects)| VariantReport {
+			id: id.clone(),
+			label: data.label(id).to_string(),
+			aspects: aspects.iter().filter(|(a, _)| !common.contains_key(*a)).map(|(a, i)| (a.clone(), *i)).collect(),
+			weather: element_weather(id, data, save),
+		}).collect();
+		let family = &data.items[&found[0].0].family;
+		ItemReport {
+			id: found[0].0.clone(),
+			label: data.families[family].label.clone(),
+			aspects: common,
+			variants,
+			weather: Vec::new(),
+		}
+	}
+
+	/// Strongest intensity of an aspect in the item or any of its variants
+	fn intensity(&self, aspect: &str) -> isize {
+		self.variants.iter().filter_map(|v| v.aspects.get(aspect))
+			.chain(self.aspects.get(aspect))
+			.copied().max().unwrap_or(0)
+	}
+
+	/// Items matching an aspect query, only from the player's items if `owned` is set, with each
+	/// family's variants grouped unless `variants` is set. Sorted by the intensity of the
+	/// `sort` aspect or else by label.
+	pub fn find(query: &str, sort: &str, owned: bool, variants: bool, data: &Data, save: &SaveData) -> Result<Vec<Self>, QueryError> {
+		let query = AspectQuery::parse(query).map_err(QueryError::Invalid)?;
+		let found = if owned {
+			find_aspected(save.items.iter()
+				.filter_map(|w| data.items.get_key_value(&w.id).map(|(id, i)| (id, w.aspects(&i.aspects)))),
+				&query, variants, data)
+		} else {
+			find_aspected(data.items.iter().map(|(id, i)| (id, i.aspects.clone())), &query, variants, data)
+		};
+		let mut found: Vec<_> = found.into_iter().map(|g| ItemReport::group(g, data, save)).collect();
+		let sort = sort.trim().to_lowercase();
+		found.sort_by(|a, b| b.intensity(&sort).cmp(&a.intensity(&sort)).then_with(|| a.label.cmp(&b.label)));
+		Ok(found)
+	}
+}
+
+/// Where a skill's soul element upgrades end up for each of its wisdom commitments
+#[derive(Serialize, Clone, Debug)]
+pub struct SkillStationReport {
+	pub id: String,
+	pub skill: String,
+	pub commitments: Vec<CommitmentReport>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct CommitmentReport {
+	pub wisdom: String,
+	/// Element of the Soul upgraded by the commitment
+	pub soul: String,
+	/// Workstations where the upgrade can happen; empty if it can't
+	pub workstations: Vec<EntityLink>,
+}
+
+fn get_skill_stations(skills: &[(&String, &Skill)], workstations: &[Workstation]) -> Vec<SkillStationReport> {
+	skills.iter().map(|(id, skill)| SkillStationReport {
+		id: id.to_string(),
+		skill: skill.label.clone(),
+		commitments: vec![
+			add_commitment(&skill.wisdoms.0, skill, workstations),
+			add_commitment(&skill.wisdoms.1, skill, workstations),
+		],
+	}).collect()
+}
+
+fn add_commitment(commit: &(String, String), skill: &Skill, workstations: &[Workstation]) -> CommitmentReport {
+	let wisdom = commit.0.split('.').nth(1)	.unwrap();
+	let soul = principles_from_soul(&commit.1);
+	let id = "e.".to_string() + wisdom;
+	let stations: Vec<_> = workstations.iter().filter(|w|
+		w.wisdoms.contains(&id) &&
+		w.accepts_principles(&[&skill.principles.0, &skill.principles.1]) &&
+		w.accepts_principles(soul.1.as_slice()))
+		.map(EntityLink::workstation).collect();
+	CommitmentReport {
+		wisdom: wisdom.to_string(),
+		soul: soul.0.to_string(),
+		workstations: stations,
+	}
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct PlanReport {
+	pub goal: Goal,
+	pub label: String,
+	/// None if there is no way to reach the goal
+	pub steps: Option<Vec<Step>>,
+}
+
+impl PlanReport {
+	pub fn new(goal: &str, data: &Data, save: &SaveData) -> Result<Self, QueryError> {
+		let goal = Goal::parse(goal, data).ok_or(QueryError::NotFound(format!("Unknown item or aspect: {}", goal)))?;
+		let label = match &goal {
+			Goal::Item(id) => data.label(id).to_string(),
+			Goal::Aspect(aspect) => aspect.clone(),
+		};
+		Ok(PlanReport {
+			steps: plan(&goal, data, save),
+			goal,
+			label,
+		})
+	}
+}
+
+/// A link to another entity's page
+#[derive(Serialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct EntityLink {
+	pub label: String,
+	pub kind: EntityKind,
+	pub id: String,
+}
+
+impl EntityLink {
+	pub fn item(id: &str, data: &Data) -> Self {
+		let memory = data.items.get(id).is_some_and(|i| i.aspects.contains_key("memory"));
+		EntityLink {
+			label: data.label(id).to_string(),
+			kind: if memory { EntityKind::Memory } else { EntityKind::Item },
+			id: id.to_string(),
+		}
+	}
+
+	pub fn book(id: &str, data: &Data) -> Self {
+		EntityLink { label: data.label(id).to_string(), kind: EntityKind::Book, id: id.to_string() }
+	}
+
+	pub fn skill(id: &str, data: &Data) -> Self {
+		EntityLink { label: data.label(id).to_string(), kind: EntityKind::Skill, id: id.to_string() }
+	}
+
+	/// A link to the page of any item, book or skill
+	pub fn any(id: &str, data: &Data) -> Option<Self> {
+		if data.items.contains_key(id) {
+			Some(Self::item(id, data))
+		} else if data.books.contains_key(id) {
+			Some(Self::book(id, data))
+		} else if data.skills.contains_key(id) {
+			Some(Self::skill(id, data))
+		} else { None }
+	}
+
+	pub fn workstation(station: &Workstation) -> Self {
+		EntityLink { label: station.label.clone(), kind: EntityKind::Workstation, id: station.id.clone() }
+	}
+}
+
+impl fmt::Display for EntityLink {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(&self.label)
+	}
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct RecipeLink {
+	pub label: String,
+	pub skill: EntityLink,
+	pub principle: String,
+	pub amount: isize,
+}
+
+impl RecipeLink {
+	fn new(recipe: &Recipe, data: &Data) -> Self {
+		RecipeLink {
+			label: recipe.label.clone(),
+			skill: EntityLink::skill(&recipe.skill, data),
+			principle: recipe.principle.clone(),
+			amount: recipe.amount,
+		}
+	}
+}
+
+/// How many of an element the player has, and where
+#[derive(Serialize, Clone, Debug)]
+pub struct Ownership {
+	pub count: usize,
+	pub locations: Vec<String>,
+}
+
+impl Ownership {
+	fn new(id: &str, data: &Data, save: &SaveData) -> Self {
+		let held: Vec<_> = save.items.iter().chain(save.skills.iter()).filter(|i| i.id == id).collect();
+		let mut locations: Vec<_> = held.iter().map(|i| save.location_label(&i.location, data).to_string()).collect();
+		locations.sort();
+		locations.dedup();
+		Ownership { count: held.len(), locations }
+	}
+}
+
+fn sorted<T: Ord>(mut v: Vec<T>) -> Vec<T> {
+	v.sort();
+	v.dedup();
+	v
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ItemDetail {
+	pub id: String,
+	pub label: String,
+	pub kind: EntityKind,
+	pub aspects: AspectMap,
+	/// Other items in the same family
+	pub variants: Vec<EntityLink>,
+	/// Seasons and weather which affect the item
+	pub weather: Vec<WeatherFlag>,
+	/// Memories gained by scrutinising or exhausting the item
+	pub yields: Vec<EntityLink>,
+	/// Items and books which give this memory
+	pub yielded_by: Vec<EntityLink>,
+	pub made_by: Vec<RecipeLink>,
+	pub used_by: Vec<RecipeLink>,
+	pub workstations: Vec<EntityLink>,
+	pub owned: Ownership,
+}
+
+impl ItemDetail {
+	pub fn new(id: &str, data: &Data, save: &SaveData) -> Option<Self> {
+		let item = data.items.get(id)?;
+		let link = EntityLink::item(id, data);
+		let mut yields = Vec::new();
+		if let Some(s) = &item.scrutiny {
+			yields.push(EntityLink::item(s, data));
+		}
+		if let ExhaustType::Beast(b) = &item.fatigues {
+			yields.push(EntityLink::item(b, data));
+		}
+		let yielded_by = data.items.iter()
+			.filter(|(_, i)| i.scrutiny.as_deref() == Some(id) || matches!(&i.fatigues, ExhaustType::Beast(b) if b == id))
+			.map(|(i, _)| EntityLink::item(i, data))
+			.chain(data.books.iter().filter(|(_, b)| b.memory == id).map(|(b, _)| EntityLink::book(b, data)))
+			.collect();
+		let made_by = data.all_recipes()
+			.filter(|r| r.effects.iter().any(|(e, _)| e == id))
+			.map(|r| RecipeLink::new(r, data)).collect();
+		let used_by = data.all_recipes()
+			.filter(|r| r.ingredient.as_ref().is_some_and(|i| i == id || item.aspects.contains_key(i)))
+			.map(|r| RecipeLink::new(r, data)).collect();
+		let workstations = data.workstations.iter()
+			.filter(|w| w.accepts(&item.aspects))
+			.map(EntityLink::workstation).collect();
+		Some(ItemDetail {
+			id: id.to_string(),
+			label: link.label,
+			kind: link.kind,
+			aspects: item.aspects.clone(),
+			variants: data.families[&item.family].members.iter()
+				.filter(|m| *m != id).map(|m| EntityLink::item(m, data)).collect(),
+			weather: element_weather(id, data, save),
+			yields,
+			yielded_by: sorted(yielded_by),
+			made_by,
+			used_by,
+			workstations: sorted(workstations),
+			owned: Ownership::new(id, data, save),
+		})
+	}
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct BookDetail {
+	pub id: String,
+	pub label: String,
+	pub aspects: AspectMap,
+	/// Principle and amount needed to master the book
+	pub mystery: Option<(String, isize)>,
+	pub lesson: EntityLink,
+	pub memory: EntityLink,
+	pub workstations: Vec<EntityLink>,
+	pub owned: Ownership,
+	pub mastered: bool,
+	/// Label of the language the book is written in, if any
+	pub language: Option<String>,
+	/// Whether the player knows the book's language, or it needs none
+	pub readable: bool,
+}
+
+impl BookDetail {
+	pub fn new(id: &str, data: &Data, save: &SaveData) -> Option<Self> {
+		let book = data.books.get(id)?;
+		let workstations = data.workstations.iter()
+			.filter(|w| w.accepts(&book.aspects))
+			.map(EntityLink::workstation).collect();
+		Some(BookDetail {
+			id: id.to_string(),
+			label: book.label.clone(),
+			aspects: book.aspects.clone(),
+			mystery: book.mystery(),
+			lesson: EntityLink::skill(&book.skill.0, data),
+			memory: EntityLink::item(&book.memory, data),
+			workstations: sorted(workstations),
+			owned: Ownership::new(id, data, save),
+			mastered: save.items.iter().any(|i| i.id == id && i.mutations.keys().any(|k| k.starts_with("mastery"))),
+			language: book.language.as_ref().map(|l| data.languages[l].clone()),
+			readable: book.language.as_ref().is_none_or(|l| save.languages.contains(l)),
+		})
+	}
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SkillDetail {
+	pub id: String,
+	pub label: String,
+	pub aspects: AspectMap,
+	pub known: bool,
+	pub commitments: Vec<CommitmentReport>,
+	pub taught_by: Vec<EntityLink>,
+	pub recipes: CraftingReport,
+}
+
+impl SkillDetail {
+	pub fn new(id: &str, data: &Data, save: &SaveData) -> Option<Self> {
+		let (id, skill) = data.skills.get_key_value(id)?;
+		let taught_by = data.books.iter()
+			.filter(|(_, b)| &b.skill.0 == id)
+			.map(|(b, _)| EntityLink::book(b, data)).collect();
+		Some(SkillDetail {
+			id: id.clone(),
+			label: skill.label.clone(),
+			aspects: skill.aspects.clone(),
+			known: save.skills.iter().any(|s| &s.id == id),
+			commitments: get_skill_stations(&[(id, skill)], &data.workstations).remove(0).commitments,
+			taught_by: sorted(taught_by),
+			recipes: CraftingReport::for_skill(id, data, save),
+		})
+	}
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct WorkstationDetail {
+	pub station: Workstation,
+	/// Room the workstation is in, if it has been unlocked
+	pub room: Option<String>,
+	/// Recipes the player's skills can craft here
+	pub recipes: Vec<RecipeLink>,
+}
+
+impl WorkstationDetail {
+	/// The workstation with the verb ID
+	pub fn new(id: &str, data: &Data, save: &SaveData) -> Option<Self> {
+		let station = data.workstations.iter().find(|w| w.id == id)?;
+		Some(Self::from_station(station, data, save))
+	}
+
+	pub fn from_station(station: &Workstation, data: &Data, save: &SaveData) -> Self {
+		let recipes = data.all_recipes()
+			.filter(|r| save.skills.iter().any(|s| s.id == r.skill) && station.can_craft(r, data))
+			.map(|r| RecipeLink::new(r, data)).collect();
+		WorkstationDetail {
+			station: station.clone(),
+			room: save.workstation_room(station, data).map(String::from),
+			recipes,
+		}
+	}
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct WorkstationsReport {
+	pub workstations: Vec<WorkstationDetail>,
+}
+
+impl WorkstationsReport {
+	/// Workstations accepting a principle, upgrading an Element of the Soul (by ID or name)
+	/// under a wisdom, and in an unlocked room, where each filter is given
+	pub fn new(principle: &str, soul: &str, wisdom: &str, unlocked: bool, data: &Data, save: &SaveData) -> Result<Self, QueryError> {
+		let principle = principle.trim().to_lowercase();
+		let soul = soul.trim().to_lowercase();
+		let soul = match soul.as_str() {
+			"" => None,
+			s => Some(souls().into_iter().find(|id| *id == s || principles_from_soul(id).0.to_lowercase() == s)
+				.ok_or(QueryError::Invalid(format!("Unknown Element of the Soul: {}", s)))?),
+		};
+		let wisdom = wisdom.trim().to_lowercase();
+		let wisdom = wisdom.trim_start_matches("e.").trim_start_matches("w.");
+
+		let mut workstations: Vec<_> = data.workstations.iter().filter(|w| {
+			(principle.is_empty() || w.principles.contains(&principle)) &&
+				soul.is_none_or(|s| w.accepts_principles(&principles_from_soul(s).1)) &&
+				(wisdom.is_empty() || w.wisdoms.iter().any(|id| id.trim_start_matches("e.") == wisdom)) &&
+				(!unlocked || save.has_workstation(w))
+		}).map(|w| WorkstationDetail::from_station(w, data, save)).collect();
+		workstations.sort_by(|a, b| a.station.label.cmp(&b.station.label));
+		Ok(WorkstationsReport { workstations })
+	}
+}
+
+/// The player's books
+#[derive(Serialize, Clone, Debug)]
+pub struct LibraryReport {
+	pub books: Vec<BookDetail>,
+}
+
+impl LibraryReport {
+	pub fn new(data: &Data, save: &SaveData) -> Self {
+		let mut ids: Vec<_> = save.items.iter().filter(|i| data.books.contains_key(&i.id)).map(|i| &i.id).collect();
+		ids.sort();
+		ids.dedup();
+		let mut books: Vec<_> = ids.into_iter().filter_map(|id| BookDetail::new(id, data, save)).collect();
+		books.sort_by(|a, b| a.label.cmp(&b.label));
+		LibraryReport { books }
+	}
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct VisitorReport {
+	pub id: String,
+	pub label: String,
+	/// Where the visitor is, if they are here now
+	pub location: Option<String>,
+	pub offers: Vec<OfferReport>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct OfferReport {
+	pub label: String,
+	/// Requirements, e.g. `Moth 5`
+	pub wants: Vec<String>,
+	/// Items, memories, books and lessons given
+	pub gives: Vec<EntityLink>,
+	/// Languages taught
+	pub languages: Vec<String>,
+	/// Anything else produced
+	pub other: Vec<String>,
+}
+
+/// Present visitors, then everyone else who might visit
+#[derive(Serialize, Clone, Debug)]
+pub struct VisitorsReport {
+	pub present: Vec<VisitorReport>,
+	pub absent: Vec<VisitorReport>,
+}
+
+impl VisitorsReport {
+	pub fn new(data: &Data, save: &SaveData) -> Self {
+		let (mut present, mut absent): (Vec<_>, Vec<_>) = data.visitors.iter().map(|(id, visitor)| VisitorReport {
+			id: id.clone(),
+			label: visitor.label.clone(),
+			location: save.visitors.iter().find(|v| &v.id == id).map(|v| save.location_label(&v.location, data).to_string()),
+			offers: visitor.offers.iter().map(|offer| OfferReport {
+				label: offer.label.clone(),
+				wants: offer.wants.iter().map(|(req, amount)| {
+					let label = if data.aspects.contains_key(req) { data.aspect_label(req) } else { data.label(req) };
+					format!("{} {}", label, amount)
+				}).collect(),
+				gives: offer.gives.iter().filter_map(|g| EntityLink::any(g, data)).collect(),
+				languages: offer.gives.iter().filter_map(|g| data.languages.get(g).cloned()).collect(),
+				other: offer.gives.iter()
+					.filter(|g| EntityLink::any(g, data).is_none() && !data.languages.contains_key(*g))
+					.map(|g| data.label(g).to_string()).collect(),
+			}).collect(),
+		}).partition(|v| v.location.is_some());
+		present.sort_by(|a, b| a.label.cmp(&b.label));
+		absent.sort_by(|a, b| a.label.cmp(&b.label));
+		VisitorsReport { present, absent }
+	}
+}
+
+/// A slot a card fits
+#[derive(Serialize, Clone, Debug)]
+pub struct TaskReport {
+	/// Verb ID
+	pub id: String,
+	/// Workstation or other verb
+	pub verb: String,
+	pub slot: String,
+	pub workstation: bool,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct AssistantReport {
+	pub id: String,
+	pub label: String,
+	pub aspects: AspectMap,
+	pub hired: bool,
+	pub tasks: Vec<TaskReport>,
+}
+
+/// Hired help and everyone else who could be hired, with the slots each can fill
+#[derive(Serialize, Clone, Debug)]
+pub struct AssistanceReport {
+	pub assistants: Vec<AssistantReport>,
+}
+
+impl AssistanceReport {
+	pub fn new(data: &Data, save: &SaveData) -> Self {
+		let mut assistants: Vec<_> = data.assistance.iter().map(|(id, a)| {
+			let hired = save.assistance.iter().find(|h| &h.id == id);
+			let aspects = hired.map(|h| h.aspects(&a.aspects)).unwrap_or_else(|| a.aspects.clone());
+			let stations = data.workstations.iter().flat_map(|w| w.slots.iter().map(move |s| (&w.id, &w.label, s, true)));
+			let verbs = data.verbs.iter().flat_map(|v| v.slots.iter().map(move |s| (&v.id, &v.label, s, false)));
+			let tasks = stations.chain(verbs)
+				.filter(|(_, _, slot, _)| !slot.required.is_empty() && slot.accepts(&aspects))
+				.map(|(id, verb, slot, workstation)| TaskReport { id: id.clone(), verb: verb.clone(), slot: slot.label.clone(), workstation })
+				.collect();
+			AssistantReport { id: id.clone(), label: a.label.clone(), aspects, hired: hired.is_some(), tasks }
+		}).collect();
+		assistants.sort_by(|a, b| b.hired.cmp(&a.hired).then_with(|| a.label.cmp(&b.label)));
+		AssistanceReport { assistants }
+	}
+}
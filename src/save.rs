@@ -8,20 +8,80 @@ use std::{
 use serde::Deserialize;
 use serde_json::Value;
 
+use crate::data::{AspectMap, Data, Workstation};
+
 #[allow(dead_code)]
 pub struct SaveData {
 	pub items: Vec<WorldItem>,
-	pub skills: Vec<String>,
-	pub abilities: Vec<String>,
+	pub skills: Vec<WorldItem>,
+	pub abilities: Vec<WorldItem>,
+	/// Unlocked rooms
+	pub rooms: Vec<String>,
+	/// Workstation verb IDs found in unlocked rooms, with the room they're in
+	pub workstations: HashMap<String, String>,
+	/// Known languages, by element ID
+	pub languages: Vec<String>,
+	/// Visitors in Brancrug or the house
+	pub visitors: Vec<WorldItem>,
+	/// Hired help
+	pub assistance: Vec<WorldItem>,
+	/// Current season and weather, as element IDs
+	pub season: Option<String>,
+	pub weather: Option<String>,
 }
 
 impl SaveData {
-	pub fn from_path(path: PathBuf) -> Self {
-		let save_file = File::open(path).expect("Failed to open save file");
+	pub fn from_path(path: PathBuf) -> Result<Self, String> {
+		let save_file = File::open(path).map_err(|e| format!("Failed to open save file: {}", e))?;
 		let save_rdr = BufReader::new(save_file);
-		let save: Save = serde_json::from_reader(save_rdr).expect("Failed to parse save file");
+		let save: Save = serde_json::from_reader(save_rdr).map_err(|e| format!("Failed to parse save file: {}", e))?;
 		save.resolve()
 	}
+
+	/// Reads the save, keeping only items known to the game data
+	pub fn load(path: PathBuf, data: &Data) -> Self {
+		Self::try_load(path, data).unwrap_or_else(|e| panic!("{}", e))
+	}
+
+	/// Reads the save like `load`, failing instead of panicking on a missing or half-written file
+	pub fn try_load(path: PathBuf, data: &Data) -> Result<Self, String> {
+		let mut save = Self::from_path(path)?;
+		let (visitors, items) = save.items.into_iter().partition(|i| data.visitors.contains_key(&i.id));
+		save.visitors = visitors;
+		let (assistance, items) = items.into_iter().partition(|i| data.assistance.contains_key(&i.id));
+		save.assistance = assistance;
+		save.items = items;
+		save.items.retain(|i|
+			data.items.contains_key(&i.id) ||
+			data.books.contains_key(&i.id) ||
+			data.skills.contains_key(&i.id));
+		save.languages.retain(|l| data.languages.contains_key(l));
+		Ok(save)
+	}
+
+	/// Whether the workstation is in an unlocked room
+	pub fn has_workstation(&self, station: &Workstation) -> bool {
+		self.workstations.contains_key(&station.id)
+	}
+
+	/// The room holding the workstation, if it is unlocked
+	pub fn workstation_room<'a>(&'a self, station: &Workstation, data: &'a Data) -> Option<&'a str> {
+		self.workstations.get(&station.id).map(|room| data.label(room))
+	}
+
+	/// A readable name for a token's location, to follow "in": the room's label, or roughly
+	/// where a sphere outside the rooms is
+	pub fn location_label<'a>(&self, location: &'a str, data: &'a Data) -> &'a str {
+		if self.rooms.iter().any(|r| r == location) {
+			data.label(location)
+		} else if location.starts_with("hand.") {
+			"your hand"
+		} else if location.starts_with("portage") {
+			"the arrivals"
+		} else {
+			"the house"
+		}
+	}
 }
 
 #[derive(Deserialize)]
@@ -32,8 +92,10 @@ struct Save {
 }
 
 impl Save {
-	fn resolve(self) -> SaveData {
+	fn resolve(self) -> Result<SaveData, String> {
 		let mut environs = self.populate_xamanek_command.current_enviro_fx_commands;
+		let season = environs.get("season").and_then(enviro_id);
+		let weather = environs.get("weather").and_then(enviro_id);
 		let non_locations = ["$type", "vignette", "sky", "weather", "music", "ui_watcher_buttons", "season", "ui_wisdoms_or_world", "meta", "run"];
 		for non in non_locations {
 			environs.remove(non);
@@ -45,25 +107,34 @@ impl Save {
 		let mut world_items = Vec::new();
 		let mut skills = Vec::new();
 		let mut abilities = Vec::new();
+		let mut workstations = HashMap::new();
+		let mut languages = Vec::new();
 		for sphere in self.root_population_command.spheres {
 			let id = sphere.governing_sphere_spec.id.clone();
 			if item_spheres.contains(&id.as_str()) {
 				for payload in sphere.resolve() {
 					world_items.push(WorldItem {
-						id: payload.entity_id.expect("No entity ID"),
+						id: payload.entity_id.ok_or("No entity ID")?,
+						token: payload.id,
+						location: id.clone(),
 						mutations: payload.mutations,
 					})
 				}
 			} else if id == "Library" {
 				for token in &sphere.tokens {
 					if locations.contains(&token.payload.id) {
+						for verb in token.payload.verbs() {
+							workstations.insert(verb, token.payload.id.clone());
+						}
 						if token.payload.id == "brancrug" {
 							for dominion in &token.payload.dominions {
 								for sphere in &dominion.spheres {
 									if !sphere.governing_sphere_spec.id.starts_with("ChristmasSlot") {
 										for payload in sphere.resolve() {
 											world_items.push(WorldItem {
-												id: payload.entity_id.expect("No entity ID"),
+												id: payload.entity_id.ok_or("No entity ID")?,
+												token: payload.id,
+												location: token.payload.id.clone(),
 												mutations: payload.mutations,
 											})
 										}
@@ -74,7 +145,9 @@ impl Save {
 						}
 						for payload in token.resolve() {
 							world_items.push(WorldItem {
-								id: payload.entity_id.expect("No entity ID"),
+								id: payload.entity_id.ok_or("No entity ID")?,
+								token: payload.id,
+								location: token.payload.id.clone(),
 								mutations: payload.mutations,
 							})
 						}
@@ -82,27 +155,74 @@ impl Save {
 				}
 			} else if id == "hand.skills" {
 				for payload in sphere.resolve() {
-					skills.push(payload.entity_id.unwrap());
+					skills.push(WorldItem {
+						id: payload.entity_id.ok_or("No entity ID")?,
+						token: payload.id,
+						location: id.clone(),
+						mutations: payload.mutations,
+					});
 				}
 			} else if id == "hand.abilities" {
 				for payload in sphere.resolve() {
-					abilities.push(payload.entity_id.unwrap());
+					abilities.push(WorldItem {
+						id: payload.entity_id.ok_or("No entity ID")?,
+						token: payload.id,
+						location: id.clone(),
+						mutations: payload.mutations,
+					});
 				}
+			} else if id == "hand.languages" {
+				languages.extend(sphere.resolve().into_iter().filter_map(|p| p.entity_id));
 			}
 		}
-		SaveData {
+		Ok(SaveData {
 			items: world_items,
 			skills,
 			abilities,
-		}
+			rooms: locations,
+			workstations,
+			languages,
+			visitors: Vec::new(),
+			assistance: Vec::new(),
+			season,
+			weather,
+		})
 	}
 }
 
+/// The element ID set by an environment command, which is either the ID itself or an
+/// object holding it in `Effect`
+fn enviro_id(command: &Value) -> Option<String> {
+	let id = match command {
+		Value::Object(fields) => fields.get("Effect")?,
+		id => id,
+	};
+	id.as_str().filter(|id| !id.is_empty()).map(String::from)
+}
+
 pub struct WorldItem {
 	pub id: String,
+	/// ID of the token in the save, unique to each copy of an element
+	pub token: String,
+	/// Room or sphere (e.g. `hand.memories`) holding the token
+	pub location: String,
 	pub mutations: HashMap<String, Value>,
 }
 
+impl WorldItem {
+	/// The element's aspects with this token's mutations applied
+	pub fn aspects(&self, base: &AspectMap) -> AspectMap {
+		let mut aspects = base.clone();
+		for (aspect, change) in &self.mutations {
+			if let Some(change) = change.as_i64() {
+				*aspects.entry(aspect.clone()).or_insert(0) += change as isize;
+			}
+		}
+		aspects.retain(|_, i| *i != 0);
+		aspects
+	}
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "PascalCase")]
 struct World {
@@ -174,10 +294,29 @@ impl Token {
 struct Payload {
 	id: String,
 	entity_id: Option<String>,
+	verb_id: Option<String>,
 	dominions: Vec<Dominion>,
 	mutations: HashMap<String, Value>,
 }
 
+impl Payload {
+	/// IDs of all situations (workstations) nested in this payload
+	fn verbs(&self) -> Vec<String> {
+		let mut verbs = Vec::new();
+		for dominion in &self.dominions {
+			for sphere in &dominion.spheres {
+				for token in &sphere.tokens {
+					if let Some(verb) = &token.payload.verb_id {
+						verbs.push(verb.clone());
+					}
+					verbs.extend(token.payload.verbs());
+				}
+			}
+		}
+		verbs
+	}
+}
+
 #[cfg(windows)]
 pub fn default_save_path() -> PathBuf {
 	use windows::Storage::UserDataPaths;
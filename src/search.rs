@@ -0,0 +1,131 @@
+use serde::Serialize;
+
+use crate::data::Data;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum EntityKind {
+	Item,
+	Memory,
+	Book,
+	Skill,
+	Workstation,
+	Recipe,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SearchResult {
+	pub kind: EntityKind,
+	pub label: String,
+	/// Item, book, skill or workstation ID; recipes use their skill's ID
+	pub id: String,
+	pub score: u32,
+}
+
+/// Typo-tolerant search over every named game entity, best match first
+pub fn search(data: &Data, query: &str, limit: usize) -> Vec<SearchResult> {
+	let query = query.trim().to_lowercase();
+	if query.is_empty() { return Vec::new() }
+
+	let mut candidates = Vec::new();
+	for (id, item) in &data.items {
+		let kind = if item.aspects.contains_key("memory") { EntityKind::Memory } else { EntityKind::Item };
+		candidates.push((kind, &item.label, id));
+	}
+	candidates.extend(data.books.iter().map(|(id, b)| (EntityKind::Book, &b.label, id)));
+	candidates.extend(data.skills.iter().map(|(id, s)| (EntityKind::Skill, &s.label, id)));
+	candidates.extend(data.workstations.iter().map(|w| (EntityKind::Workstation, &w.label, &w.id)));
+	candidates.extend(data.all_recipes().map(|r| (EntityKind::Recipe, &r.label, &r.skill)));
+
+	let mut found: Vec<_> = candidates.into_iter().filter_map(|(kind, label, id)| {
+		score(&query, &label.to_lowercase()).map(|score| SearchResult {
+			kind,
+			label: label.clone(),
+			id: id.clone(),
+			score,
+		})
+	}).collect();
+	found.sort_by(|a, b| b.score.cmp(&a.score)
+		.then_with(|| a.label.len().cmp(&b.label.len()))
+		.then_with(|| a.label.cmp(&b.label)));
+	found.dedup_by(|a, b| a.kind == b.kind && a.label == b.label);
+	found.truncate(limit);
+	found
+}
+
+/// How well a lowercase label matches a lowercase query, or None if it doesn't.
+/// Whole substring matches rank above matching every query word with a few typos.
+fn score(query: &str, label: &str) -> Option<u32> {
+	if label == query { return Some(1000) }
+	if label.starts_with(query) { return Some(900) }
+	if let Some(pos) = label.find(query) {
+		return Some(800 - pos.min(100) as u32);
+	}
+
+	let words: Vec<_> = label.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()).collect();
+	let mut total = 0;
+	for q in query.split_whitespace() {
+		let allowed = match q.chars().count() {
+			0..=3 => 0,
+			4..=6 => 1,
+			_ => 2,
+		};
+		let best = words.iter().map(|w| {
+			if w.starts_with(q) { return 0 }
+			// Compare against the word and against a prefix of the word the length of the query
+			let prefix: String = w.chars().take(q.chars().count()).collect();
+			distance(q, w).min(distance(q, &prefix))
+		}).min()?;
+		if best > allowed { return None }
+		total += best;
+	}
+	Some(500u32.saturating_sub(total as u32 * 50))
+}
+
+/// Levenshtein edit distance
+fn distance(a: &str, b: &str) -> usize {
+	let b: Vec<_> = b.chars().collect();
+	let mut prev: Vec<_> = (0..=b.len()).collect();
+	for (i, ca) in a.chars().enumerate() {
+		let mut cur = vec![i + 1];
+		for (j, cb) in b.iter().enumerate() {
+			let sub = prev[j] + usize::from(ca != *cb);
+			cur.push(sub.min(prev[j + 1] + 1).min(cur[j] + 1));
+		}
+		prev = cur;
+	}
+	prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn distance_counts_edits() {
+		assert_eq!(distance("", ""), 0);
+		assert_eq!(distance("lantern", "lantern"), 0);
+		assert_eq!(distance("lantren", "lantern"), 2);
+		assert_eq!(distance("moth", "month"), 1);
+		assert_eq!(distance("", "abc"), 3);
+	}
+
+	#[test]
+	fn score_ranks_closer_matches_higher() {
+		let exact = score("candle", "candle").unwrap();
+		let prefix = score("candle", "candle (lit)").unwrap();
+		let inside = score("candle", "beeswax candle").unwrap();
+		let typo = score("candke", "beeswax candle").unwrap();
+		assert!(exact > prefix && prefix > inside && inside > typo);
+	}
+
+	#[test]
+	fn score_limits_typos_by_length() {
+		assert!(score("cat", "bat").is_none());
+		assert!(score("candke", "candle").is_some());
+		assert!(score("candel", "candle").is_none());
+		assert!(score("cnadel", "candle").is_none());
+		assert!(score("candle bees", "beeswax candle").is_some());
+		assert!(score("candle oil", "beeswax candle").is_none());
+	}
+}
@@ -0,0 +1,297 @@
+use std::{cmp::Reverse, collections::HashSet, ptr};
+
+use serde::Serialize;
+
+use crate::data::*;
+use crate::logic::{element_weather, find_memories, PrincipleQuery, WeatherFlag};
+use crate::report::EntityLink;
+use crate::save::SaveData;
+
+/// How many candidates to try in each of the memory and item slots
+const SLOT_CANDIDATES: usize = 4;
+/// How many solutions to report per workstation
+pub const MAX_SOLUTIONS: usize = 10;
+
+/// What the player wants to achieve at a workstation
+#[derive(Serialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Target {
+	Principle(PrincipleQuery),
+	Recipe(Recipe),
+	Lesson { book: String, label: String, principle: String, amount: isize },
+}
+
+impl Target {
+	/// Interprets a principle query (`lantern>=10`), recipe label or book label
+	pub fn parse(input: &str, data: &Data) -> Option<Self> {
+		let input = input.trim();
+		if let Some(q) = PrincipleQuery::parse(input) {
+			if principles().contains(&q.aspect.as_str()) {
+				return Some(Target::Principle(q));
+			}
+		}
+		let lower = input.to_lowercase();
+		let recipes = data.all_recipes();
+		if let Some(r) = recipes.clone().find(|r| r.label.to_lowercase() == lower)
+			.or_else(|| recipes.clone().find(|r| r.label.to_lowercase().starts_with(&lower)))
+		{
+			return Some(Target::Recipe(r.clone()));
+		}
+		// An exact label, else the shortest label it starts
+		let mut books: Vec<_> = data.books.iter().filter(|(_, b)| b.label.to_lowercase().starts_with(&lower)).collect();
+		books.sort_by_key(|(id, b)| (b.label.to_lowercase() != lower, b.label.len(), *id));
+		let book = books.first()?;
+		let (principle, amount) = book.1.mystery()?;
+		Some(Target::Lesson {
+			book: book.0.clone(),
+			label: book.1.label.clone(),
+			principle,
+			amount,
+		})
+	}
+
+	pub fn principle(&self) -> &str {
+		match self {
+			Target::Principle(q) => &q.aspect,
+			Target::Recipe(r) => &r.principle,
+			Target::Lesson { principle, .. } => principle,
+		}
+	}
+
+	pub fn amount(&self) -> isize {
+		match self {
+			Target::Principle(q) => q.min,
+			Target::Recipe(r) => r.amount,
+			Target::Lesson { amount, .. } => *amount,
+		}
+	}
+}
+
+/// A card the player could place in a slot
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct Card {
+	pub id: String,
+	pub label: String,
+	pub aspects: AspectMap,
+	/// Seasons and weather which affect the card
+	pub weather: Vec<WeatherFlag>,
+}
+
+impl Card {
+	fn value(&self, principle: &str) -> isize {
+		self.aspects.get(principle).copied().unwrap_or(0)
+	}
+}
+
+/// A card placed in a workstation slot
+#[derive(Serialize, Clone, Debug)]
+pub struct Placed {
+	pub slot: String,
+	pub card: Card,
+}
+
+/// One way of filling a workstation's slots
+#[derive(Serialize, Clone, Debug)]
+pub struct Solution {
+	pub workstation: EntityLink,
+	pub slots: Vec<Placed>,
+	pub total: isize,
+}
+
+impl Solution {
+	pub fn cards(&self) -> impl Iterator<Item = &Card> {
+		self.slots.iter().map(|p| &p.card)
+	}
+}
+
+/// The player's cards, resolved against game data
+pub struct Hand {
+	pub skills: Vec<Card>,
+	pub souls: Vec<Card>,
+	pub memories: Vec<Card>,
+	pub items: Vec<Card>,
+}
+
+impl Hand {
+	pub fn new(data: &Data, save: &SaveData) -> Self {
+		let skills = save.skills.iter().filter_map(|s| data.skills.get(&s.id).map(|skill| Card {
+			id: s.id.clone(),
+			label: skill.label.clone(),
+			aspects: s.aspects(&skill.aspects),
+			weather: Vec::new(),
+		})).collect();
+		let souls = save.abilities.iter().filter_map(|a| data.abilities.get(&a.id).map(|ability| Card {
+			id: a.id.clone(),
+			label: ability.label.clone(),
+			aspects: a.aspects(&ability.aspects),
+			weather: Vec::new(),
+		})).collect();
+
+		let mut memories = Vec::new();
+		let mut items = Vec::new();
+		let mut seen = HashSet::new();
+		for item in &save.items {
+			if !seen.insert(&item.token) { continue }
+			let card = if let Some(i) = data.items.get(&item.id) {
+				Card { id: item.id.clone(), label: i.label.clone(), aspects: item.aspects(&i.aspects), weather: element_weather(&item.id, data, save) }
+			} else if let Some(b) = data.books.get(&item.id) {
+				Card { id: item.id.clone(), label: b.label.clone(), aspects: item.aspects(&b.aspects), weather: Vec::new() }
+			} else { continue };
+			if card.aspects.contains_key("memory") {
+				memories.push(card);
+			} else {
+				items.push(card);
+			}
+		}
+
+		for helper in &save.assistance {
+			if !seen.insert(&helper.token) { continue }
+			let Some(a) = data.assistance.get(&helper.id) else { continue };
+			items.push(Card { id: helper.id.clone(), label: a.label.clone(), aspects: helper.aspects(&a.aspects), weather: Vec::new() });
+		}
+
+		// Memories which aren't held yet but can be had from owned items
+		let queries: Vec<_> = principles().into_iter().map(|p| PrincipleQuery { aspect: p.to_string(), min: 1 }).collect();
+		for mem in find_memories(&queries, &save.items, &data.items, &data.books) {
+			if memories.iter().any(|m: &Card| m.label == mem.label) { continue }
+			let mut sources: Vec<_> = mem.sources.into_iter().collect();
+			sources.sort();
+			memories.push(Card {
+				weather: element_weather(&mem.id, data, save),
+				id: mem.id,
+				label: format!("{} (from {})", mem.label, sources.join(", ")),
+				aspects: mem.aspects,
+			});
+		}
+
+		Hand { skills, souls, memories, items }
+	}
+}
+
+/// Finds the strongest ways of meeting the target at a workstation
+pub fn solve_station(station: &Workstation, target: &Target, hand: &Hand, data: &Data) -> Vec<Solution> {
+	let principle = target.principle();
+	if !station.principles.iter().any(|p| p == principle) {
+		return Vec::new();
+	}
+
+	// Card which has to be slotted for the target, if any
+	let required = |c: &Card| match target {
+		Target::Principle(_) => false,
+		Target::Recipe(r) => r.ingredient.as_ref().is_some_and(|i| &c.id == i || c.aspects.contains_key(i)),
+		Target::Lesson { book, .. } => &c.id == book,
+	};
+	let needs_card = match target {
+		Target::Principle(_) => false,
+		Target::Recipe(r) => {
+			if !station.can_craft(r, data) { return Vec::new() }
+			r.ingredient.is_some() && station.special == SpecialWorkstation::None
+		},
+		Target::Lesson { .. } => true,
+	};
+
+	let options: Vec<_> = station.slots.iter().map(|slot| {
+		// The kind already decides which skills, souls and memories go in a slot; only item
+		// slots need their aspects checked
+		let fits = |c: &&Card| slot.accepts(&c.aspects);
+		let opts = match slot.kind {
+			// Every solution needs a skill, so the first skill slot can't be left empty
+			SlotKind::Skill => {
+				let first = station.slots.iter().find(|s| s.kind == SlotKind::Skill).is_some_and(|s| ptr::eq(s, slot));
+				let mut opts = if first { Vec::new() } else { vec![None] };
+				opts.extend(hand.skills.iter().filter(|s| match target {
+					Target::Recipe(r) => s.id == r.skill,
+					_ => true,
+				}).map(Some));
+				opts
+			},
+			SlotKind::Soul => options(hand.souls.iter().filter(|c| c.value(principle) > 0), principle, usize::MAX),
+			SlotKind::Memory => options(hand.memories.iter().filter(|c| c.value(principle) > 0), principle, SLOT_CANDIDATES),
+			SlotKind::Item => {
+				let mut opts = options(hand.items.iter().filter(fits).filter(|c| c.value(principle) > 0 && !required(c)), principle, SLOT_CANDIDATES);
+				opts.extend(hand.items.iter().filter(fits).filter(|c| required(c)).map(Some));
+				opts
+			},
+		};
+		(slot, opts)
+	}).collect();
+
+	if !station.slots.iter().any(|s| s.kind == SlotKind::Skill) {
+		return Vec::new();
+	}
+
+	let mut solutions = Vec::new();
+	let search = Combinations::new(&options, principle, target.amount());
+	search.fill(0, &mut Vec::new(), 0, &mut |combo, total| {
+		if needs_card && !combo.iter().any(|(_, c)| required(c)) { return }
+		solutions.push(Solution {
+			workstation: EntityLink::workstation(station),
+			slots: combo.iter().map(|(slot, card)| Placed { slot: slot.label.clone(), card: (*card).clone() }).collect(),
+			total,
+		});
+	});
+	solutions.sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.cards().count().cmp(&b.cards().count())));
+	solutions.truncate(MAX_SOLUTIONS);
+	solutions
+}
+
+/// Solves the target at every workstation, best workstation first
+pub fn solve_all(target: &Target, hand: &Hand, data: &Data) -> Vec<Vec<Solution>> {
+	let mut res: Vec<_> = data.workstations.iter()
+		.map(|w| solve_station(w, target, hand, data))
+		.filter(|s| !s.is_empty())
+		.collect();
+	res.sort_by(|a, b| b[0].total.cmp(&a[0].total));
+	res
+}
+
+/// Every way of filling the slots from their options which reaches a minimum total, never
+/// using a card twice
+struct Combinations<'a, 'b> {
+	options: &'b [(&'a Slot, Vec<Option<&'a Card>>)],
+	principle: &'b str,
+	min: isize,
+	/// Most the slots from each index on could still add
+	bounds: Vec<isize>,
+}
+
+impl<'a, 'b> Combinations<'a, 'b> {
+	fn new(options: &'b [(&'a Slot, Vec<Option<&'a Card>>)], principle: &'b str, min: isize) -> Self {
+		let mut bounds = vec![0; options.len() + 1];
+		for (i, (_, opts)) in options.iter().enumerate().rev() {
+			let best = opts.iter().flatten().map(|c| c.value(principle)).max().unwrap_or(0);
+			bounds[i] = bounds[i + 1] + best.max(0);
+		}
+		Combinations { options, principle, min, bounds }
+	}
+
+	/// Fills the slots from `i` on, calling `visit` with each complete combination and its total.
+	/// Gives up early on combinations which can't reach the minimum any more.
+	fn fill(&self, i: usize, combo: &mut Vec<(&'a Slot, &'a Card)>, total: isize, visit: &mut impl FnMut(&[(&'a Slot, &'a Card)], isize)) {
+		if total + self.bounds[i] < self.min { return }
+		let Some((slot, opts)) = self.options.get(i) else {
+			return visit(combo, total);
+		};
+		for opt in opts {
+			match opt {
+				None => self.fill(i + 1, combo, total, visit),
+				Some(card) if combo.iter().any(|(_, c)| ptr::eq(*c, *card)) => (),
+				Some(card) => {
+					combo.push((slot, card));
+					self.fill(i + 1, combo, total + card.value(self.principle), visit);
+					combo.pop();
+				},
+			}
+		}
+	}
+}
+
+/// The strongest cards for a slot, plus leaving it empty
+fn options<'a>(cards: impl Iterator<Item = &'a Card>, principle: &str, max: usize) -> Vec<Option<&'a Card>> {
+	let mut cards: Vec<_> = cards.collect();
+	cards.sort_by_key(|c| Reverse(c.value(principle)));
+	cards.truncate(max);
+	let mut opts = vec![None];
+	opts.extend(cards.into_iter().map(Some));
+	opts
+}
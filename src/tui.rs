@@ -0,0 +1,272 @@
+use std::{
+	fs,
+	io,
+	path::PathBuf,
+	time::{Duration, Instant, SystemTime},
+};
+
+use ratatui::{
+	crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+	layout::{Constraint, Layout},
+	style::{Color, Modifier, Style},
+	text::Line,
+	widgets::{Block, List, ListItem, ListState, Paragraph, Tabs, Wrap},
+	DefaultTerminal, Frame,
+};
+
+use crate::data::Data;
+use crate::planner::Step;
+use crate::report::*;
+use crate::save::SaveData;
+use crate::ui::{dis_aspects, dis_principles, dis_set, dis_vec, dis_weather};
+
+/// How often to check the save file for changes
+const SAVE_POLL: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+	Memories,
+	Items,
+	Crafting,
+	Slots,
+	Plan,
+}
+
+impl Mode {
+	const ALL: [Mode; 5] = [Mode::Memories, Mode::Items, Mode::Crafting, Mode::Slots, Mode::Plan];
+
+	fn title(&self) -> &'static str {
+		match self {
+			Mode::Memories => "Memories",
+			Mode::Items => "Items",
+			Mode::Crafting => "Crafting",
+			Mode::Slots => "Solver",
+			Mode::Plan => "Planner",
+		}
+	}
+
+	fn hint(&self) -> &'static str {
+		match self {
+			Mode::Memories => "Principles, e.g. lantern>=5, moth:2",
+			Mode::Items => "Aspects, e.g. lantern & tool & !fragile",
+			Mode::Crafting => "Skill",
+			Mode::Slots => "Principle total, recipe or book, e.g. lantern>=10",
+			Mode::Plan => "Item, memory or aspect",
+		}
+	}
+}
+
+/// A result line and the text shown in the detail pane when it is selected
+struct Entry {
+	title: String,
+	detail: Vec<String>,
+}
+
+struct Tui<'a> {
+	data: &'a Data,
+	save: SaveData,
+	save_path: PathBuf,
+	modified: Option<SystemTime>,
+	mode: Mode,
+	query: String,
+	status: String,
+	results: Vec<Entry>,
+	selected: ListState,
+}
+
+/// Runs the terminal UI until the user quits
+pub fn run(data: &Data, save: SaveData, save_path: PathBuf) -> io::Result<()> {
+	let mut terminal = ratatui::init();
+	let modified = fs::metadata(&save_path).and_then(|m| m.modified()).ok();
+	let mut tui = Tui {
+		data,
+		save,
+		save_path,
+		modified,
+		mode: Mode::Memories,
+		query: String::new(),
+		status: String::from("Type a query and press Enter. Tab switches mode, Esc or Ctrl-C quits."),
+		results: Vec::new(),
+		selected: ListState::default(),
+	};
+	let res = tui.run(&mut terminal);
+	ratatui::restore();
+	res
+}
+
+impl Tui<'_> {
+	fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+		let mut last_poll = Instant::now();
+		loop {
+			terminal.draw(|f| self.draw(f))?;
+			if event::poll(Duration::from_millis(250))? {
+				if let Event::Key(key) = event::read()? {
+					if key.kind != KeyEventKind::Press { continue }
+					match key.code {
+						KeyCode::Esc => return Ok(()),
+						KeyCode::Tab | KeyCode::BackTab => {
+							let i = Mode::ALL.iter().position(|m| *m == self.mode).unwrap();
+							let next = if key.code == KeyCode::Tab { i + 1 } else { i + Mode::ALL.len() - 1 };
+							self.mode = Mode::ALL[next % Mode::ALL.len()];
+							self.query.clear();
+							self.results.clear();
+							self.selected.select(None);
+						},
+						KeyCode::Enter => self.search(),
+						KeyCode::Backspace => { self.query.pop(); },
+						KeyCode::Up => self.selected.select_previous(),
+						KeyCode::Down => self.selected.select_next(),
+						// Raw mode delivers Ctrl-C as a key press rather than a signal
+						KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+						KeyCode::Char(c) => self.query.push(c),
+						_ => (),
+					}
+				}
+			}
+			if last_poll.elapsed() >= SAVE_POLL {
+				last_poll = Instant::now();
+				self.reload();
+			}
+		}
+	}
+
+	/// Reloads the save if it changed on disk, then reruns the current query
+	fn reload(&mut self) {
+		let time = fs::metadata(&self.save_path).and_then(|m| m.modified()).ok();
+		if time.is_some() && time != self.modified {
+			self.modified = time;
+			match SaveData::try_load(self.save_path.clone(), self.data) {
+				Ok(save) => {
+					self.save = save;
+					if !self.query.is_empty() {
+						self.search();
+					}
+					self.status = String::from("Save reloaded");
+				},
+				// Probably caught mid-write; keep the old save and retry on the next change
+				Err(e) => {
+					self.modified = None;
+					self.status = format!("Keeping the previous save: {}", e);
+				},
+			}
+		}
+	}
+
+	fn search(&mut self) {
+		let (data, save, query) = (self.data, &self.save, self.query.as_str());
+		let res = match self.mode {
+			Mode::Memories => {
+				let report = MemoryReport::new(query, data, save);
+				Ok(report.memories.into_iter().map(|m| (m, "From")).chain(report.craftable.into_iter().map(|m| (m, "Craft")))
+					.map(|(m, how)| Entry {
+						title: format!("{} ({}){}", m.label, dis_principles(&m.aspects, data), dis_weather(&m.weather)),
+						detail: vec![format!("{} {}", how, dis_set(&m.sources))],
+					}).collect())
+			},
+			Mode::Items => ItemReport::find(query, "", false, false, data, save).map(|found| found.into_iter().map(|i| {
+				let mut detail = vec![dis_aspects(&i.aspects, data)];
+				for v in i.variants {
+					detail.push(format!("{}{} ({})", v.label, dis_weather(&v.weather), dis_aspects(&v.aspects, data)));
+				}
+				Entry { title: format!("{}{}", i.label, dis_weather(&i.weather)), detail }
+			}).collect()),
+			Mode::Crafting => CraftingReport::new(query, data, save).map(|report| report.recipes.into_iter().map(|r| {
+				let mut detail = vec![
+					format!("{} recipe, needs {} {}", r.level, data.aspect_label(&r.principle), r.amount),
+				];
+				if let Some(i) = r.ingredient {
+					detail.push(format!("Using {}", i));
+				}
+				for m in r.makes {
+					detail.push(format!("Makes {}x {} ({})", m.quantity, m.label, dis_principles(&m.aspects, data)));
+					if let Some(mem) = m.memory {
+						detail.push(format!("  Scrutinise for {} ({})", mem.label, dis_principles(&mem.aspects, data)));
+					}
+				}
+				let stations: Vec<_> = r.workstations.into_iter().map(|w| w.label).collect();
+				detail.push(match stations.len() {
+					0 => String::from("No unlocked workstation can craft this"),
+					_ => format!("Craft at {}", dis_vec(&stations)),
+				});
+				Entry {
+					title: format!("{}{}{}", r.label, if r.known { "" } else { " [New Recipe!]" }, dis_weather(&r.weather)),
+					detail,
+				}
+			}).collect()),
+			Mode::Slots => SlotReport::new("", query, data, save).map(|report| report.workstations.into_iter()
+				.flat_map(|s| s.into_iter())
+				.map(|s| Entry {
+					title: format!("{} {} at {}", s.total, data.aspect_label(report.target.principle()), s.workstation),
+					detail: s.slots.iter().map(|p| format!("{}: {}{} ({})", p.slot, p.card.label, dis_weather(&p.card.weather), dis_principles(&p.card.aspects, data))).collect(),
+				}).collect()),
+			Mode::Plan => PlanReport::new(query, data, save).map(|report| match report.steps {
+				None => vec![Entry { title: format!("No plan found for {}", report.label), detail: Vec::new() }],
+				Some(steps) => steps.into_iter().map(|step| match step {
+					Step::Have { item } => Entry { title: format!("Take {}", item), detail: Vec::new() },
+					Step::Recall { item, memory } => Entry {
+						title: format!("Remember {}", memory),
+						detail: vec![format!("Scrutinise or read {}", item)],
+					},
+					Step::Craft { recipe, skill, workstations, ingredient, output } => Entry {
+						title: format!("Craft {}", output),
+						detail: [
+							Some(format!("Recipe: {}", recipe)),
+							Some(format!("Skill: {}", skill)),
+							ingredient.map(|i| format!("Using {}", i)),
+							Some(format!("At {}", dis_vec(&workstations))),
+						].into_iter().flatten().collect(),
+					},
+				}).collect(),
+			}),
+		};
+		match res {
+			Ok(results) => {
+				self.status = format!("{} results", results.len());
+				self.results = results;
+				self.selected.select((!self.results.is_empty()).then_some(0));
+			},
+			Err(e) => {
+				self.status = e.to_string();
+				self.results.clear();
+				self.selected.select(None);
+			},
+		}
+	}
+
+	fn draw(&mut self, frame: &mut Frame) {
+		let [tabs, input, body, status] = Layout::vertical([
+			Constraint::Length(1),
+			Constraint::Length(3),
+			Constraint::Min(0),
+			Constraint::Length(1),
+		]).areas(frame.area());
+		let [list, detail] = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(body);
+
+		let selected_tab = Mode::ALL.iter().position(|m| *m == self.mode).unwrap();
+		frame.render_widget(Tabs::new(Mode::ALL.iter().map(|m| m.title()))
+			.select(selected_tab)
+			.highlight_style(Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD)), tabs);
+
+		let query = if self.query.is_empty() {
+			Paragraph::new(self.mode.hint()).style(Style::new().fg(Color::DarkGray))
+		} else {
+			Paragraph::new(self.query.as_str())
+		};
+		frame.render_widget(query.block(Block::bordered().title("Search")), input);
+
+		let items: Vec<_> = self.results.iter().map(|e| ListItem::new(e.title.as_str())).collect();
+		frame.render_stateful_widget(List::new(items)
+			.block(Block::bordered().title("Results"))
+			.highlight_style(Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD)), list, &mut self.selected);
+
+		let lines: Vec<_> = self.selected.selected()
+			.and_then(|i| self.results.get(i))
+			.map(|e| e.detail.iter().map(|l| Line::from(l.as_str())).collect())
+			.unwrap_or_default();
+		frame.render_widget(Paragraph::new(lines)
+			.wrap(Wrap { trim: false })
+			.block(Block::bordered().title("Details")), detail);
+
+		frame.render_widget(Paragraph::new(self.status.as_str()).style(Style::new().fg(Color::DarkGray)), status);
+	}
+}
@@ -1,5 +1,14 @@
+use std::{collections::HashSet, fmt};
+
 use maud::{html, Markup};
 
+use crate::data::{principles, AspectMap, Data, Skill, SpecialWorkstation};
+use crate::logic::{Memory, WeatherFlag};
+use crate::planner::{Goal, Step};
+use crate::report::*;
+use crate::search::{EntityKind, SearchResult};
+use crate::solver::Target;
+
 pub fn base_layout(title: &str, content: Markup) -> Markup {
 	html! {
 		(maud::DOCTYPE)
@@ -10,18 +19,620 @@ pub fn base_layout(title: &str, content: Markup) -> Markup {
 				meta name="color-scheme" content="dark";
 				meta name="viewport" content="width=device-width, initial-scale=1.0";
 				link rel="stylesheet" href="/assets/main.css";
+				script src="/assets/suggest.js" defer {}
 				title { (title) }
 			}
 			body {
 				header {
+					form .search method="get" action="/search" {
+						input .textbox type="search" name="q" placeholder="Search";
+					}
 					a .hbutton href = "/" {"Main"}
 					a .hbutton href = "/find_mems" {"Find Memories"}
 					a .hbutton href = "/solve" {"Solver"}
 					a .hbutton href = "/crafting" {"Crafting"}
+					a .hbutton href = "/plan" {"Planner"}
 					a .hbutton href = "/items" {"Items Browser"}
+					a .hbutton href = "/workstations" {"Workstations"}
+					a .hbutton href = "/library" {"Library"}
+					a .hbutton href = "/visitors" {"Visitors"}
+					a .hbutton href = "/assistance" {"Assistance"}
 				}
 				(content)
 			}
 		}
 	}
 }
+
+/// Percent-encodes an ID or label for use as a path segment
+pub fn path_segment(s: &str) -> String {
+	s.bytes().map(|b| match b {
+		b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+		b => format!("%{:02X}", b),
+	}).collect()
+}
+
+pub fn entity_url(kind: EntityKind, id: &str) -> String {
+	match kind {
+		EntityKind::Item | EntityKind::Memory => format!("/item/{}", path_segment(id)),
+		EntityKind::Book => format!("/book/{}", path_segment(id)),
+		EntityKind::Skill | EntityKind::Recipe => format!("/skill/{}", path_segment(id)),
+		EntityKind::Workstation => format!("/workstation/{}", path_segment(id)),
+	}
+}
+
+pub fn search_results(query: &str, results: &[SearchResult]) -> Markup {
+	html! {
+		h2 { "Results for " (query) }
+		@if results.is_empty() {
+			p { "Nothing found" }
+		}
+		@for r in results {
+			p {
+				a href=(entity_url(r.kind, &r.id)) { (r.label) }
+				" (" (format!("{:?}", r.kind).to_lowercase()) ")"
+			}
+		}
+	}
+}
+
+fn link(kind: EntityKind, id: &str, label: &str) -> Markup {
+	html! { a href=(entity_url(kind, id)) { (label) } }
+}
+
+fn links(links: &[EntityLink]) -> Markup {
+	html! {
+		@for (i, l) in links.iter().enumerate() {
+			@if i > 0 { ", " }
+			(link(l.kind, &l.id, &l.label))
+		}
+	}
+}
+
+fn recipe_links(recipes: &[RecipeLink], data: &Data) -> Markup {
+	html! {
+		ul {
+			@for r in recipes {
+				li { (r.label) " with " (link(r.skill.kind, &r.skill.id, &r.skill.label)) " (" (data.aspect_label(&r.principle)) " " (r.amount) ")" }
+			}
+		}
+	}
+}
+
+fn aspect_list(aspects: &AspectMap, data: &Data) -> Markup {
+	let mut aspects: Vec<_> = aspects.iter().filter(|(a, _)| !data.aspect_hidden(a))
+		.map(|(a, i)| (data.aspect_label(a), i, data.aspects.get(a).map(|a| a.description.as_str()).unwrap_or_default()))
+		.collect();
+	aspects.sort();
+	html! {
+		p {
+			@for (aspect, intensity, description) in aspects {
+				span title=(description) { (aspect) } ": " (intensity) br;
+			}
+		}
+	}
+}
+
+fn sorted_keys(aspects: &AspectMap) -> Vec<String> {
+	let mut keys: Vec<_> = aspects.keys().cloned().collect();
+	keys.sort();
+	keys
+}
+
+fn aspect_labels(aspects: &[String], data: &Data) -> String {
+	aspects.iter().map(|a| data.aspect_label(a)).collect::<Vec<_>>().join(", ")
+}
+
+fn ownership(owned: &Ownership) -> Markup {
+	html! {
+		@if owned.count == 0 {
+			p { "You don't have any" }
+		} @else {
+			p { "You have " (owned.count) " in " (owned.locations.join(", ")) }
+		}
+	}
+}
+
+pub fn item_detail(item: &ItemDetail, data: &Data) -> Markup {
+	html! {
+		h2 { (item.label) }
+		p { @if item.kind == EntityKind::Memory { "Memory" } @else { "Item" } }
+		(aspect_list(&item.aspects, data))
+		@if !item.weather.is_empty() {
+			p { "Changes with" (dis_weather(&item.weather)) }
+		}
+		(ownership(&item.owned))
+		@if !item.variants.is_empty() {
+			p { "Variants: " (links(&item.variants)) }
+		}
+		@if !item.yields.is_empty() {
+			p { "Scrutinise or exhaust for " (links(&item.yields)) }
+		}
+		@if !item.yielded_by.is_empty() {
+			p { "Remembered from " (links(&item.yielded_by)) }
+		}
+		@if !item.made_by.is_empty() {
+			h3 { "Made by" }
+			(recipe_links(&item.made_by, data))
+		}
+		@if !item.used_by.is_empty() {
+			h3 { "Used by" }
+			(recipe_links(&item.used_by, data))
+		}
+		@if !item.workstations.is_empty() {
+			h3 { "Accepted at" }
+			p { (links(&item.workstations)) }
+		}
+	}
+}
+
+pub fn book_detail(book: &BookDetail, data: &Data) -> Markup {
+	html! {
+		h2 { (book.label) }
+		p { "Book" }
+		@if let Some((principle, amount)) = &book.mystery {
+			p { "Requires " (data.aspect_label(principle)) " " (amount) " to master" }
+		}
+		@if let Some(language) = &book.language {
+			p {
+				"Needs " (language)
+				@if !book.readable { " (you can't read it yet)" }
+			}
+		}
+		p { "Teaches " (link(book.lesson.kind, &book.lesson.id, &book.lesson.label)) }
+		p { "Remembers " (link(book.memory.kind, &book.memory.id, &book.memory.label)) }
+		(ownership(&book.owned))
+		@if book.mastered { p { "You have mastered this book" } }
+		@if !book.workstations.is_empty() {
+			h3 { "Accepted at" }
+			p { (links(&book.workstations)) }
+		}
+	}
+}
+
+pub fn library(report: &LibraryReport, data: &Data) -> Markup {
+	html! {
+		@if report.books.is_empty() {
+			p { "You have no books" }
+		}
+		table {
+			tr { th { "Book" } th { "To master" } th { "Language" } th { "Status" } }
+			@for book in &report.books {
+				tr {
+					td { (link(EntityKind::Book, &book.id, &book.label)) }
+					td {
+						@if let Some((principle, amount)) = &book.mystery {
+							(data.aspect_label(principle)) " " (amount)
+						}
+					}
+					td { (book.language.as_deref().unwrap_or("")) }
+					td {
+						@if book.mastered { "Mastered" }
+						@else if !book.readable { "Can't read yet" }
+						@else { "Not mastered" }
+					}
+				}
+			}
+		}
+	}
+}
+
+fn visitor(v: &VisitorReport) -> Markup {
+	html! {
+		h3 { (v.label) @if let Some(location) = &v.location { " (in " (location) ")" } }
+		@if v.offers.is_empty() {
+			p { "Nothing known" }
+		}
+		ul {
+			@for offer in &v.offers {
+				li {
+					(offer.label)
+					@if !offer.wants.is_empty() { " — wants " (offer.wants.join(", ")) }
+					@if !offer.languages.is_empty() { " — teaches " (offer.languages.join(", ")) }
+					@if !offer.gives.is_empty() { " — gives " (links(&offer.gives)) }
+					@if !offer.other.is_empty() { " — " (offer.other.join(", ")) }
+				}
+			}
+		}
+	}
+}
+
+pub fn visitors(report: &VisitorsReport) -> Markup {
+	html! {
+		h2 { "Visitors here" }
+		@if report.present.is_empty() {
+			p { "Nobody is visiting" }
+		}
+		@for v in &report.present { (visitor(v)) }
+		h2 { "Other visitors" }
+		@for v in &report.absent { (visitor(v)) }
+	}
+}
+
+pub fn assistance(report: &AssistanceReport, data: &Data) -> Markup {
+	html! {
+		@if report.assistants.iter().all(|a| !a.hired) {
+			p { "You haven't hired anyone" }
+		}
+		@for a in &report.assistants {
+			h3 { (a.label) @if a.hired { " (hired)" } }
+			p { (dis_aspects(&a.aspects, data)) }
+			@if a.tasks.is_empty() {
+				p { "No known use" }
+			}
+			ul {
+				@for task in &a.tasks {
+					li {
+						@if task.workstation {
+							(link(EntityKind::Workstation, &task.id, &task.verb))
+						} @else {
+							(task.verb)
+						}
+						": " (task.slot)
+					}
+				}
+			}
+		}
+	}
+}
+
+pub fn skill_detail(skill: &SkillDetail, data: &Data) -> Markup {
+	html! {
+		h2 { (skill.label) }
+		p { "Skill" @if skill.known { " (known)" } }
+		p { (dis_principles(&skill.aspects, data)) }
+		@for c in &skill.commitments {
+			(commitment(c))
+		}
+		@if !skill.taught_by.is_empty() {
+			p { "Taught by " (links(&skill.taught_by)) }
+		}
+		(crafting(&skill.recipes, data))
+	}
+}
+
+pub fn workstation_detail(detail: &WorkstationDetail, data: &Data) -> Markup {
+	let station = &detail.station;
+	html! {
+		h2 { (station.label) }
+		p { "Workstation" @if let Some(room) = &detail.room { " in " (room) } }
+		p { "Principles: " (aspect_labels(&station.principles, data)) }
+		@if !station.wisdoms.is_empty() {
+			p { "Wisdoms: " (station.wisdoms.iter().map(|w| data.aspect_label(w).trim_start_matches("e.")).collect::<Vec<_>>().join(", ")) }
+		}
+		h3 { "Slots" }
+		ul {
+			@for slot in &station.slots {
+				li {
+					(slot.label)
+					@if !slot.required.is_empty() { ": any of " (aspect_labels(&sorted_keys(&slot.required), data)) }
+					@if !slot.essential.is_empty() { ", needs " (aspect_labels(&sorted_keys(&slot.essential), data)) }
+					@if !slot.forbidden.is_empty() { ", not " (aspect_labels(&sorted_keys(&slot.forbidden), data)) }
+				}
+			}
+		}
+		@if !detail.recipes.is_empty() {
+			h3 { "Recipes you can craft here" }
+			(recipe_links(&detail.recipes, data))
+		}
+	}
+}
+
+pub fn workstations(report: &WorkstationsReport, data: &Data) -> Markup {
+	html! {
+		@if report.workstations.is_empty() {
+			p { "No matching workstations" }
+		}
+		table {
+			tr { th { "Workstation" } th { "Room" } th { "Principles" } th { "Wisdoms" } th { "Accepts" } th { "Special" } }
+			@for detail in &report.workstations {
+				@let station = &detail.station;
+				tr {
+					td { (link(EntityKind::Workstation, &station.id, &station.label)) }
+					td { (detail.room.as_deref().unwrap_or("Locked")) }
+					td { (aspect_labels(&station.principles, data)) }
+					td { (station.wisdoms.iter().map(|w| data.aspect_label(w).trim_start_matches("e.")).collect::<Vec<_>>().join(", ")) }
+					td {
+						@for slot in station.item_slots() {
+							(aspect_labels(&sorted_keys(&slot.required), data)) br;
+						}
+					}
+					td {
+						@match station.special {
+							SpecialWorkstation::Kitchen => "Kitchen",
+							SpecialWorkstation::Instrument => "Instrument",
+							SpecialWorkstation::None => "",
+						}
+					}
+				}
+			}
+		}
+	}
+}
+
+/// Workstation links as "A, B or C", each given by ID, label and room if known
+fn station_links<'a>(stations: impl ExactSizeIterator<Item = (&'a str, &'a str, Option<&'a str>)>) -> Markup {
+	let len = stations.len();
+	html! {
+		@for (i, (id, label, room)) in stations.enumerate() {
+			@if i > 0 { @if i + 1 == len { " or " } @else { ", " } }
+			(link(EntityKind::Workstation, id, label))
+			@if let Some(room) = room { " (" (room) ")" }
+		}
+	}
+}
+
+pub fn skill_stations(reports: &[SkillStationReport]) -> Markup {
+	html! {
+		@for report in reports {
+			h3 { (link(EntityKind::Skill, &report.id, &report.skill)) }
+			@for c in &report.commitments {
+				(commitment(c))
+			}
+		}
+	}
+}
+
+fn commitment(c: &CommitmentReport) -> Markup {
+	let stations = c.workstations.iter().map(|s| (s.id.as_str(), s.label.as_str(), None));
+	html! {
+		@if c.workstations.is_empty() {
+			p { "Warning: " (c.soul) " can't be upgraded when committed to " (c.wisdom) }
+		} @else {
+			p { (c.soul) " is upgraded at " (station_links(stations)) " when committed to " (c.wisdom) }
+		}
+	}
+}
+
+fn memory(mem: &Memory, data: &Data) -> Markup {
+	html! {
+		h3 { (link(EntityKind::Memory, &mem.id, &mem.label)) " (" (dis_principles(&mem.aspects, data)) ")" (dis_weather(&mem.weather)) }
+	}
+}
+
+pub fn memories(report: &MemoryReport, data: &Data) -> Markup {
+	html! {
+		@if !report.now.is_empty() {
+			p { "Now: " (report.now.join(", ")) }
+		}
+		@for mem in &report.memories {
+			(memory(mem, data))
+			p { (dis_set(&mem.sources)) }
+		}
+		@if !report.craftable.is_empty() {
+			h2 { "Craftable Memories" }
+			@for mem in &report.craftable {
+				(memory(mem, data))
+				p { "Scrutinise the result of " (dis_set(&mem.sources)) }
+			}
+		}
+	}
+}
+
+pub fn solve(report: &SolveReport, data: &Data) -> Markup {
+	html! {
+		@if report.skills.is_empty() {
+			h2 { "No Matching Skills" }
+		} @else {
+			h2 { "Matching skills" }
+			(skill_stations(&report.skills))
+		}
+		@if report.memories.is_empty() {
+			h2 { "No Matching Memories" }
+		} @else {
+			h2 { "Matching Memories" }
+			@for mem in &report.memories {
+				(memory(mem, data))
+				p { (dis_set(&mem.sources)) }
+			}
+		}
+	}
+}
+
+pub fn slots(report: &SlotReport, data: &Data) -> Markup {
+	html! {
+		h2 {
+			@match &report.target {
+				Target::Principle(q) => { "Reaching " (data.aspect_label(&q.aspect)) " " (q.min) },
+				Target::Recipe(r) => { "Crafting " (r.label) " (" (data.aspect_label(&r.principle)) " " (r.amount) ")" },
+				Target::Lesson { label, principle, amount, .. } => { "Studying " (label) " (" (data.aspect_label(principle)) " " (amount) ")" },
+			}
+		}
+		@if !report.now.is_empty() {
+			p { "Now: " (report.now.join(", ")) }
+		}
+		@if report.workstations.iter().all(|s| s.is_empty()) {
+			p { "No way to meet the requirement with what you have" }
+		}
+		@for station in report.workstations.iter().filter(|s| !s.is_empty()) {
+			h3 { (link(station[0].workstation.kind, &station[0].workstation.id, &station[0].workstation.label)) }
+			@for s in station {
+				p {
+					(s.total) " " (data.aspect_label(report.target.principle())) ": "
+					@for (i, p) in s.slots.iter().enumerate() {
+						@if i > 0 { " + " }
+						span title=(p.slot) {
+							@match EntityLink::any(&p.card.id, data) {
+								Some(l) => (link(l.kind, &l.id, &p.card.label)),
+								None => (p.card.label),
+							}
+							(dis_weather(&p.card.weather))
+						}
+					}
+				}
+			}
+		}
+	}
+}
+
+pub fn crafting(report: &CraftingReport, data: &Data) -> Markup {
+	html! {
+		h2 { "Using skill " (link(EntityKind::Skill, &report.id, &report.skill)) }
+		@for (i, r) in report.recipes.iter().enumerate() {
+			@if i == 0 || report.recipes[i - 1].level != r.level {
+				h3 { (r.level) " recipes:" }
+			}
+			p {
+				(r.label)
+				@if let Some(i) = &r.ingredient { " using " (i) }
+				" (" (data.aspect_label(&r.principle)) " " (r.amount) ")"
+				@if !r.known { " [New Recipe!]" }
+				(dis_weather(&r.weather))
+				@for made in &r.makes {
+					br;
+					"Makes " (made.quantity) "x " (link(EntityKind::Item, &made.id, &made.label))
+					@if !made.aspects.is_empty() { " (" (dis_principles(&made.aspects, data)) ")" }
+					@if let Some(mem) = &made.memory {
+						", scrutinise for " (link(EntityKind::Memory, &mem.id, &mem.label)) " (" (dis_principles(&mem.aspects, data)) ")"
+					}
+				}
+				br;
+				@if r.workstations.is_empty() {
+					"No unlocked workstation can craft this"
+				} @else {
+					"Craft at " (station_links(r.workstations.iter().map(|w| (w.id.as_str(), w.label.as_str(), w.room.as_deref()))))
+				}
+			}
+		}
+	}
+}
+
+/// Lets the player pick between several skills matching what they typed
+pub fn choose_skill(input: &str, skills: &[(&String, &Skill)]) -> Markup {
+	html! {
+		h2 { "Several skills start with " (input) }
+		@for (_, skill) in skills {
+			form method="post" action="/crafting" {
+				input type="hidden" name="skill" value=(skill.label);
+				input type="submit" value=(skill.label);
+			}
+		}
+	}
+}
+
+pub fn plan(report: &PlanReport) -> Markup {
+	html! {
+		h2 {
+			@match report.goal {
+				Goal::Item(_) => { "Plan for " (report.label) },
+				Goal::Aspect(_) => { "Plan for something with " (report.label) },
+			}
+		}
+		@match &report.steps {
+			None => p { "No plan found with your skills, workstations and items" },
+			Some(steps) => ol {
+				@for step in steps {
+					li {
+						@match step {
+							Step::Have { item } => { "Take " (link(item.kind, &item.id, &item.label)) },
+							Step::Recall { item, memory } => {
+								"Scrutinise or read " (link(item.kind, &item.id, &item.label))
+								" to remember " (link(memory.kind, &memory.id, &memory.label))
+							},
+							Step::Craft { recipe, skill, workstations, ingredient, output } => {
+								"Craft " (link(output.kind, &output.id, &output.label)) " (" (recipe) ") with " (link(skill.kind, &skill.id, &skill.label))
+								@if let Some(i) = ingredient { " using " (i) }
+								" at " (links(workstations))
+							},
+						}
+					}
+				}
+			},
+		}
+	}
+}
+
+pub fn items(found: &[ItemReport], data: &Data) -> Markup {
+	html! {
+		@for item in found {
+			h3 { (link(EntityKind::Item, &item.id, &item.label)) (dis_weather(&item.weather)) }
+			p { (dis_aspects(&item.aspects, data)) }
+			@if !item.variants.is_empty() {
+				details {
+					summary { (item.variants.len()) " variants" }
+					ul {
+						@for v in &item.variants {
+							li {
+								(link(EntityKind::Item, &v.id, &v.label)) (dis_weather(&v.weather))
+								@if !v.aspects.is_empty() { ": " (dis_aspects(&v.aspects, data)) }
+							}
+						}
+					}
+				}
+			}
+		}
+	}
+}
+
+pub fn dis_vec<T: fmt::Display>(v: &[T]) -> String {
+	match v.len() {
+		0 => String::new(),
+		1 => v[0].to_string(),
+		2 => format!("{} or {}", v[0], v[1]),
+		l => {
+			let mut res = String::new();
+			for name in &v[..l-1] {
+				res.push_str(&fmt_name(name.to_string()));
+				res.push_str(", ");
+			}
+			res.push_str("or ");
+			res.push_str(&fmt_name(v[l-1].to_string()));
+			res
+		}
+	}
+}
+
+pub fn dis_set(v: &HashSet<String>) -> String {
+	match v.len() {
+		0 => String::new(),
+		1 => v.iter().next().unwrap().to_string(),
+		2 => {
+			let mut it = v.iter();
+			format!("{} or {}", it.next().unwrap(), it.next().unwrap())
+		},
+		_ => {
+			let mut qty = 0;
+			let mut res = String::new();
+			let mut it = v.iter().peekable();
+			loop {
+				let item = it.next().unwrap();
+				if it.peek().is_none() || qty == 8 {
+					res.push_str("or ");
+					res.push_str(&fmt_name(item.to_string()));
+					return res;
+				}
+				res.push_str(&fmt_name(item.to_string()));
+				res.push_str(", ");
+				qty += 1;
+			}
+		}
+	}
+}
+
+fn fmt_name(name: String) -> String {
+	if name.contains(',') {
+		format!("'{}'", name)
+	} else { name }
+}
+
+/// Lists the principle intensities of an aspect map, strongest first, e.g. `Lantern 4, Sky 2`
+pub fn dis_principles(aspects: &AspectMap, data: &Data) -> String {
+	let mut p: Vec<_> = aspects.iter().filter(|(a, _)| principles().contains(&a.as_str())).collect();
+	p.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+	p.into_iter().map(|(a, i)| format!("{} {}", data.aspect_label(a), i)).collect::<Vec<_>>().join(", ")
+}
+
+/// Seasons and weather affecting something, e.g. ` [Rain (now), Winter]`
+pub fn dis_weather(flags: &[WeatherFlag]) -> String {
+	if flags.is_empty() { return String::new() }
+	let flags: Vec<_> = flags.iter().map(|f| if f.now { format!("{} (now)", f.label) } else { f.label.clone() }).collect();
+	format!(" [{}]", flags.join(", "))
+}
+
+/// Lists the aspects the game shows, by label, e.g. `Lantern: 4, Tool: 1`
+pub fn dis_aspects(aspects: &AspectMap, data: &Data) -> String {
+	let mut shown: Vec<_> = aspects.iter().filter(|(a, _)| !data.aspect_hidden(a))
+		.map(|(a, i)| format!("{}: {}", data.aspect_label(a), i)).collect();
+	shown.sort();
+	shown.join(", ")
+}